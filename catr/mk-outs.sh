@@ -11,6 +11,7 @@ EMPTY="$ROOT/empty.txt"
 FOX="$ROOT/fox.txt"
 SPIDERS="$ROOT/spiders.txt"
 BUSTLE="$ROOT/the-bustle.txt"
+CONTROL="$ROOT/control.txt"
 ALL="$EMPTY $FOX $SPIDERS $BUSTLE"
 
 for FILE in $ALL; do
@@ -27,3 +28,46 @@ cat -b $ALL > $OUT_DIR/all.b.out
 cat    < $BUSTLE > $OUT_DIR/$(basename $BUSTLE).stdin.out
 cat -n < $BUSTLE > $OUT_DIR/$(basename $BUSTLE).n.stdin.out
 cat -b < $BUSTLE > $OUT_DIR/$(basename $BUSTLE).b.stdin.out
+
+for FLAG in v E T A e t; do
+    cat -$FLAG $CONTROL > $OUT_DIR/$(basename $CONTROL).$FLAG.out
+done
+cat -n -A $CONTROL > $OUT_DIR/$(basename $CONTROL).n.A.out
+cat -b -T $CONTROL > $OUT_DIR/$(basename $CONTROL).b.T.out
+
+BLANKS="$ROOT/blanks.txt"
+cat -s    $BLANKS > $OUT_DIR/$(basename $BLANKS).s.out
+cat -s -n $BLANKS > $OUT_DIR/$(basename $BLANKS).s.n.out
+cat -s -b $BLANKS > $OUT_DIR/$(basename $BLANKS).s.b.out
+cat -s -n $BLANKS $EMPTY $BLANKS > $OUT_DIR/blanks2.s.n.out
+
+CRLF="$ROOT/crlf.txt"
+BINARY="$ROOT/binary.bin"
+cat -n $CRLF > $OUT_DIR/$(basename $CRLF).n.out
+cat -b $CRLF > $OUT_DIR/$(basename $CRLF).b.out
+cat -n $BINARY > $OUT_DIR/$(basename $BINARY).n.out
+cat -v $BINARY > $OUT_DIR/$(basename $BINARY).v.out
+cat $BINARY $CRLF > $OUT_DIR/binary_crlf.out
+
+zcat -f $FOX.gz $SPIDERS $FOX > $OUT_DIR/decompress.out
+
+xxd $BINARY > $OUT_DIR/$(basename $BINARY).x.out
+xxd -c 8 -g 3 $BUSTLE > $OUT_DIR/$(basename $BUSTLE).x.c8.g3.out
+xxd -g 0 -s 0x10 -l 40 $BUSTLE > $OUT_DIR/$(basename $BUSTLE).x.g0.s16.l40.out
+cat $FOX $SPIDERS | xxd > $OUT_DIR/fox_spiders.x.out
+
+LATIN1="$ROOT/latin1.txt"
+iconv -f latin1 -t utf-8 $LATIN1 > $OUT_DIR/$(basename $LATIN1).utf8.out
+iconv -f latin1 -t utf-8 $LATIN1 | cat -n > $OUT_DIR/$(basename $LATIN1).utf8.n.out
+iconv -f utf-8 -t utf-16be $BUSTLE > $OUT_DIR/$(basename $BUSTLE).utf16be.out
+cat -n $BUSTLE | iconv -f utf-8 -t utf-16 > $OUT_DIR/$(basename $BUSTLE).n.utf16.out
+
+nl -ba -w3 -s': ' $SPIDERS > $OUT_DIR/$(basename $SPIDERS).n.w3.s.out
+nl -ba -nrz -w4 -v10 -i5 $BUSTLE > $OUT_DIR/$(basename $BUSTLE).rz.w4.v10.i5.out
+(nl -ba -nln $FOX; nl -ba -nln $SPIDERS) > $OUT_DIR/fox_spiders.ln.per_file.out
+
+cat -n $BUSTLE | sed -n '3,6p' > $OUT_DIR/$(basename $BUSTLE).n.range3-6.out
+cat -n $ALL | sed -n '4,$p' | head -n -2 > $OUT_DIR/all.n.range4--3.out
+cat $ALL | tail -n 4 > $OUT_DIR/all.range-4.out
+tail -n 3 $BUSTLE > $OUT_DIR/$(basename $BUSTLE).range-3.stdin.out
+cat -n $BUSTLE | tail -n 4 | head -n 3 > $OUT_DIR/$(basename $BUSTLE).n.range-4-8.out
@@ -0,0 +1,401 @@
+use encoding_rs::{
+    CoderResult, Decoder, DecoderResult, Encoder, EncoderResult, Encoding,
+    UTF_16BE, UTF_16LE, UTF_8,
+};
+use std::fmt;
+use std::io::{self, BufRead, Read, Write};
+
+// Codificações aceitas por --from-encoding e --to-encoding
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Charset {
+    // o encoding_rs segue o padrão WHATWG, em que "iso-8859-1" é um apelido
+    // de windows-1252; aqui o Latin-1 de verdade mapeia cada byte para o
+    // code point de mesmo valor, como o iconv
+    Latin1,
+    // "utf-16" sem sufixo escreve o BOM na saída; na entrada o BOM, se
+    // existir, sempre decide a ordem dos bytes
+    Utf16 { big_endian: bool, bom: bool },
+    // utf-8, iso-8859-2..16, windows-1250..1258 e os demais rótulos do
+    // encoding_rs
+    Other(&'static Encoding),
+}
+
+impl fmt::Display for Charset {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Charset::Latin1 => write!(f, "ISO-8859-1"),
+            Charset::Utf16 { bom: true, .. } => write!(f, "UTF-16"),
+            Charset::Utf16 { big_endian: true, .. } => write!(f, "UTF-16BE"),
+            Charset::Utf16 { .. } => write!(f, "UTF-16LE"),
+            Charset::Other(encoding) => write!(f, "{}", encoding.name()),
+        }
+    }
+}
+
+// --------------------------------------------------
+// Converte o nome informado pelo usuário em uma codificação
+pub fn parse_charset(label: &str) -> Option<Charset> {
+    match label.to_ascii_lowercase().as_str() {
+        "latin1" | "latin-1" | "iso-8859-1" | "iso8859-1" | "l1" => {
+            Some(Charset::Latin1)
+        }
+        "utf-16" | "utf16" => Some(Charset::Utf16 {
+            big_endian: false,
+            bom: true,
+        }),
+        "utf-16le" | "utf16le" => Some(Charset::Utf16 {
+            big_endian: false,
+            bom: false,
+        }),
+        "utf-16be" | "utf16be" => Some(Charset::Utf16 {
+            big_endian: true,
+            bom: false,
+        }),
+        "utf8" => Some(Charset::Other(UTF_8)),
+        // alguns rótulos do WHATWG (como "replacement") não podem ser usados
+        // para escrever, então só aceitamos os que codificam para si mesmos
+        label => Encoding::for_label(label.as_bytes())
+            .filter(|encoding| encoding.output_encoding() == *encoding)
+            .map(Charset::Other),
+    }
+}
+
+fn invalid_data(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+// --------------------------------------------------
+// Leitor que decodifica a entrada da codificação escolhida para utf8, de
+// forma que o restante do catr (numeração, -v, conversão de finais de
+// linha) continue trabalhando apenas com utf8. No modo estrito uma sequência
+// inválida interrompe a leitura informando o offset dela na entrada; no
+// modo normal ela vira o caractere de substituição U+FFFD
+pub struct DecodeReader<R> {
+    inner: R,
+    // None para Latin-1, que não precisa de estado
+    decoder: Option<Decoder>,
+    charset: Charset,
+    strict: bool,
+    // bytes da entrada já entregues ao decodificador
+    offset: u64,
+    done: bool,
+    error: Option<io::Error>,
+    pending: String,
+    pos: usize,
+}
+
+impl<R: BufRead> DecodeReader<R> {
+    pub fn new(inner: R, charset: Charset, strict: bool) -> Self {
+        // new_decoder reconhece e remove o BOM de utf8 e utf16
+        let decoder = match charset {
+            Charset::Latin1 => None,
+            Charset::Utf16 {
+                big_endian: true, ..
+            } => Some(UTF_16BE.new_decoder()),
+            Charset::Utf16 { .. } => Some(UTF_16LE.new_decoder()),
+            Charset::Other(encoding) => Some(encoding.new_decoder()),
+        };
+        DecodeReader {
+            inner,
+            decoder,
+            charset,
+            strict,
+            offset: 0,
+            done: false,
+            error: None,
+            pending: String::new(),
+            pos: 0,
+        }
+    }
+
+    // decodifica a próxima porção da entrada em pending
+    fn refill(&mut self) -> io::Result<()> {
+        self.pending.clear();
+        self.pos = 0;
+        // o texto válido antes de uma sequência inválida já foi entregue;
+        // agora é a vez do erro
+        if let Some(err) = self.error.take() {
+            return Err(err);
+        }
+        while self.pending.is_empty() && !self.done {
+            let chunk = self.inner.fill_buf()?.to_vec();
+            self.inner.consume(chunk.len());
+            // uma leitura vazia indica o fim do arquivo; o decodificador
+            // ainda precisa ser avisado para reclamar de uma sequência
+            // incompleta no final
+            let last = chunk.is_empty();
+            self.done = last;
+            let decoded = match self.decoder.as_mut() {
+                None => {
+                    self.pending.extend(chunk.iter().map(|&b| b as char));
+                    Ok(())
+                }
+                Some(decoder) => decode(
+                    decoder,
+                    &chunk,
+                    &mut self.pending,
+                    last,
+                    self.strict,
+                ),
+            };
+            if let Err(pos) = decoded {
+                let err = invalid_data(format!(
+                    "invalid {} sequence at byte offset {}",
+                    self.charset,
+                    self.offset as i64 + pos
+                ));
+                self.done = true;
+                if self.pending.is_empty() {
+                    return Err(err);
+                }
+                self.error = Some(err);
+            }
+            self.offset += chunk.len() as u64;
+        }
+        Ok(())
+    }
+}
+
+// --------------------------------------------------
+// Decodifica src acrescentando o texto em dst. No modo estrito devolve a
+// posição, relativa ao início de src, da primeira sequência inválida; ela
+// pode ser negativa quando a sequência começou na leitura anterior
+fn decode(
+    decoder: &mut Decoder,
+    src: &[u8],
+    dst: &mut String,
+    last: bool,
+    strict: bool,
+) -> Result<(), i64> {
+    let mut read = 0;
+    loop {
+        // os métodos *_to_string escrevem apenas na capacidade livre
+        let needed = decoder
+            .max_utf8_buffer_length(src.len() - read)
+            .unwrap_or(src.len() * 3 + 16);
+        dst.reserve(needed);
+        let remaining = &src[read..];
+        if strict {
+            let (result, len) = decoder.decode_to_string_without_replacement(
+                remaining, dst, last,
+            );
+            match result {
+                DecoderResult::InputEmpty => return Ok(()),
+                DecoderResult::OutputFull => read += len,
+                DecoderResult::Malformed(bad, extra) => {
+                    return Err((read + len) as i64 - extra as i64 - bad as i64);
+                }
+            }
+        } else {
+            let (result, len, _) =
+                decoder.decode_to_string(remaining, dst, last);
+            read += len;
+            if result == CoderResult::InputEmpty {
+                return Ok(());
+            }
+        }
+    }
+}
+
+impl<R: BufRead> Read for DecodeReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pos == self.pending.len() {
+            self.refill()?;
+        }
+        let available = &self.pending.as_bytes()[self.pos..];
+        let len = available.len().min(buf.len());
+        buf[..len].copy_from_slice(&available[..len]);
+        self.pos += len;
+        Ok(len)
+    }
+}
+
+// --------------------------------------------------
+// Escritor que recebe utf8 e escreve na codificação escolhida. Fica do lado
+// da saída para que os números de linha e as marcas de -E e -T também sejam
+// convertidos. Um caractere que não existe na codificação de destino vira
+// "?" ou, no modo estrito, interrompe o programa informando o offset em que
+// ele seria escrito na saída
+pub struct EncodeWriter<W: Write> {
+    inner: W,
+    encoder: Option<Encoder>,
+    charset: Charset,
+    strict: bool,
+    // bytes já escritos em inner
+    offset: u64,
+    // o utf8 pode chegar dividido entre duas chamadas de write
+    partial: Vec<u8>,
+    bom_written: bool,
+}
+
+impl<W: Write> EncodeWriter<W> {
+    pub fn new(inner: W, charset: Charset, strict: bool) -> Self {
+        let encoder = match charset {
+            Charset::Other(encoding) if encoding != UTF_8 => {
+                Some(encoding.new_encoder())
+            }
+            _ => None,
+        };
+        EncodeWriter {
+            inner,
+            encoder,
+            charset,
+            strict,
+            offset: 0,
+            partial: Vec::new(),
+            bom_written: false,
+        }
+    }
+
+    fn encode(&mut self, text: &str) -> io::Result<()> {
+        let mut text = text.to_string();
+        loop {
+            let mut out = Vec::with_capacity(text.len() * 2);
+            if let Charset::Utf16 { bom: true, .. } = self.charset {
+                if !self.bom_written {
+                    out.extend_from_slice(&[0xff, 0xfe]);
+                }
+            }
+            self.bom_written = true;
+            let unmappable = match (self.charset, self.encoder.as_mut()) {
+                (Charset::Latin1, _) => encode_latin1(&text, &mut out),
+                (Charset::Utf16 { big_endian, .. }, _) => {
+                    encode_utf16(&text, big_endian, &mut out);
+                    None
+                }
+                (_, Some(encoder)) => encode_with(encoder, &text, &mut out),
+                (_, None) => {
+                    out.extend_from_slice(text.as_bytes());
+                    None
+                }
+            };
+            // o que veio antes do caractere problemático é escrito mesmo
+            // no modo estrito
+            self.inner.write_all(&out)?;
+            self.offset += out.len() as u64;
+            match unmappable {
+                None => return Ok(()),
+                Some((ch, _)) if self.strict => {
+                    return Err(invalid_data(format!(
+                        "cannot convert {:?} (U+{:04X}) to {} at output \
+                         offset {}",
+                        ch, ch as u32, self.charset, self.offset
+                    )));
+                }
+                // continua a partir do caractere, agora trocado por "?"
+                Some((ch, pos)) => {
+                    text = format!("?{}", &text[pos + ch.len_utf8()..]);
+                }
+            }
+        }
+    }
+
+    // bytes que não formam utf8 válido (só acontece quando a entrada não
+    // foi decodificada com --from-encoding)
+    fn invalid_utf8(&mut self, bytes: &[u8]) -> io::Result<()> {
+        if self.strict {
+            return Err(invalid_data(format!(
+                "invalid UTF-8 sequence {:02x?} at output offset {}",
+                bytes, self.offset
+            )));
+        }
+        self.encode("?")
+    }
+}
+
+impl<W: Write> Write for EncodeWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.partial.extend_from_slice(buf);
+        let mut bytes = std::mem::take(&mut self.partial);
+        loop {
+            let err = match std::str::from_utf8(&bytes) {
+                Ok(text) => {
+                    let text = text.to_string();
+                    self.encode(&text)?;
+                    break;
+                }
+                Err(err) => err,
+            };
+            let valid = err.valid_up_to();
+            let text = String::from_utf8_lossy(&bytes[..valid]).to_string();
+            self.encode(&text)?;
+            match err.error_len() {
+                Some(len) => {
+                    let invalid = bytes[valid..valid + len].to_vec();
+                    self.invalid_utf8(&invalid)?;
+                    bytes.drain(..valid + len);
+                }
+                // sequência incompleta no fim: espera o resto no próximo
+                // write
+                None => {
+                    self.partial = bytes.split_off(valid);
+                    break;
+                }
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        // o que sobrou incompleto até aqui não vai mais ser completado
+        if !self.partial.is_empty() {
+            let partial = std::mem::take(&mut self.partial);
+            self.invalid_utf8(&partial)?;
+        }
+        self.inner.flush()
+    }
+}
+
+// --------------------------------------------------
+// As funções abaixo acrescentam o texto codificado em out e param no
+// primeiro caractere sem representação, devolvendo-o junto com a sua
+// posição em text
+
+fn encode_with(
+    encoder: &mut Encoder,
+    text: &str,
+    out: &mut Vec<u8>,
+) -> Option<(char, usize)> {
+    let mut read = 0;
+    loop {
+        // os métodos *_to_vec escrevem apenas na capacidade livre
+        let needed = encoder
+            .max_buffer_length_from_utf8_without_replacement(text.len() - read)
+            .unwrap_or(text.len() * 4 + 16);
+        out.reserve(needed);
+        let (result, len) = encoder.encode_from_utf8_to_vec_without_replacement(
+            &text[read..],
+            out,
+            false,
+        );
+        read += len;
+        match result {
+            EncoderResult::InputEmpty => return None,
+            EncoderResult::OutputFull => {}
+            // read já inclui o caractere que não pôde ser convertido
+            EncoderResult::Unmappable(ch) => {
+                return Some((ch, read - ch.len_utf8()))
+            }
+        }
+    }
+}
+
+fn encode_latin1(text: &str, out: &mut Vec<u8>) -> Option<(char, usize)> {
+    for (pos, ch) in text.char_indices() {
+        match u8::try_from(ch as u32) {
+            Ok(byte) => out.push(byte),
+            Err(_) => return Some((ch, pos)),
+        }
+    }
+    None
+}
+
+fn encode_utf16(text: &str, big_endian: bool, out: &mut Vec<u8>) {
+    for unit in text.encode_utf16() {
+        if big_endian {
+            out.extend_from_slice(&unit.to_be_bytes());
+        } else {
+            out.extend_from_slice(&unit.to_le_bytes());
+        }
+    }
+}
@@ -0,0 +1,265 @@
+use std::fmt;
+use std::io::{self, BufRead, Read};
+
+// marca de ordem de bytes (BOM) do utf8
+const UTF8_BOM: &[u8] = b"\xef\xbb\xbf";
+
+// Terminador de linha usado na saída por --to-lf e --to-crlf
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LineEnding {
+    Lf,
+    Crlf,
+}
+
+impl LineEnding {
+    fn as_bytes(&self) -> &'static [u8] {
+        match self {
+            LineEnding::Lf => b"\n",
+            LineEnding::Crlf => b"\r\n",
+        }
+    }
+}
+
+// --------------------------------------------------
+// Leitor que reescreve os terminadores de linha enquanto os bytes passam por
+// ele, sem precisar carregar o arquivo inteiro. LF, CRLF e CR sozinho (como
+// nos arquivos do Mac antigo) são todos convertidos para o terminador
+// escolhido. Também pode remover o BOM do início do arquivo
+pub struct ConvertEol<R> {
+    inner: R,
+    target: Option<LineEnding>,
+    strip_bom: bool,
+    // o BOM só é procurado na primeira leitura
+    started: bool,
+    // o último byte convertido foi um \r, então um \n logo em seguida faz
+    // parte do mesmo CRLF, mesmo que chegue na próxima leitura
+    prev_cr: bool,
+    // bytes já convertidos que ainda não couberam no buffer de quem leu
+    pending: Vec<u8>,
+    pos: usize,
+}
+
+impl<R: BufRead> ConvertEol<R> {
+    pub fn new(inner: R, target: Option<LineEnding>, strip_bom: bool) -> Self {
+        ConvertEol {
+            inner,
+            target,
+            strip_bom,
+            started: false,
+            prev_cr: false,
+            pending: Vec::new(),
+            pos: 0,
+        }
+    }
+
+    // converte um pedaço da entrada, acumulando o resultado em pending
+    fn convert(&mut self, chunk: &[u8]) {
+        let target = match self.target {
+            Some(target) => target,
+            None => {
+                self.pending.extend_from_slice(chunk);
+                return;
+            }
+        };
+        for &byte in chunk {
+            match byte {
+                b'\r' => {
+                    self.pending.extend_from_slice(target.as_bytes());
+                    self.prev_cr = true;
+                }
+                // o terminador já foi escrito quando o \r apareceu
+                b'\n' if self.prev_cr => self.prev_cr = false,
+                b'\n' => self.pending.extend_from_slice(target.as_bytes()),
+                _ => {
+                    self.pending.push(byte);
+                    self.prev_cr = false;
+                }
+            }
+        }
+    }
+
+    // preenche pending com a próxima porção convertida da entrada
+    fn refill(&mut self) -> io::Result<()> {
+        self.pending.clear();
+        self.pos = 0;
+        if !self.started {
+            self.started = true;
+            // lê os três primeiros bytes de uma vez, já que o BOM pode ser
+            // dividido entre duas leituras de um pipe
+            let mut head = Vec::with_capacity(UTF8_BOM.len());
+            self.inner
+                .by_ref()
+                .take(UTF8_BOM.len() as u64)
+                .read_to_end(&mut head)?;
+            if !(self.strip_bom && head == UTF8_BOM) {
+                self.convert(&head);
+            }
+            if !self.pending.is_empty() {
+                return Ok(());
+            }
+        }
+        // repete até produzir algo ou chegar ao fim do arquivo: um \n que
+        // completa um CRLF não gera nenhum byte
+        loop {
+            let chunk = self.inner.fill_buf()?;
+            if chunk.is_empty() {
+                return Ok(());
+            }
+            let chunk = chunk.to_vec();
+            self.inner.consume(chunk.len());
+            self.convert(&chunk);
+            if !self.pending.is_empty() {
+                return Ok(());
+            }
+        }
+    }
+}
+
+impl<R: BufRead> Read for ConvertEol<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pos == self.pending.len() {
+            self.refill()?;
+        }
+        let available = &self.pending[self.pos..];
+        let len = available.len().min(buf.len());
+        buf[..len].copy_from_slice(&available[..len]);
+        self.pos += len;
+        Ok(len)
+    }
+}
+
+// --------------------------------------------------
+// Quantidade de cada tipo de terminador encontrada por --detect
+#[derive(Debug, Default, PartialEq)]
+pub struct EolStats {
+    pub lf: usize,
+    pub crlf: usize,
+    pub cr: usize,
+    pub bom: bool,
+}
+
+impl fmt::Display for EolStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let kinds = [(self.lf, "LF"), (self.crlf, "CRLF"), (self.cr, "CR")];
+        let found: Vec<&str> = kinds
+            .iter()
+            .filter(|(count, _)| *count > 0)
+            .map(|(_, name)| *name)
+            .collect();
+        match found.as_slice() {
+            [] => write!(f, "none")?,
+            [kind] => write!(f, "{}", kind)?,
+            _ => write!(
+                f,
+                "mixed ({} LF, {} CRLF, {} CR)",
+                self.lf, self.crlf, self.cr
+            )?,
+        }
+        if self.bom {
+            write!(f, ", with BOM")?;
+        }
+        Ok(())
+    }
+}
+
+// --------------------------------------------------
+// Conta os terminadores de linha do arquivo lendo-o em blocos
+pub fn detect(mut file: impl BufRead) -> io::Result<EolStats> {
+    let mut stats = EolStats::default();
+    let mut prev_cr = false;
+    let mut started = false;
+    loop {
+        let chunk = file.fill_buf()?;
+        if chunk.is_empty() {
+            break;
+        }
+        if !started {
+            started = true;
+            stats.bom = chunk.starts_with(UTF8_BOM);
+        }
+        for &byte in chunk {
+            match byte {
+                b'\n' if prev_cr => {
+                    stats.crlf += 1;
+                    prev_cr = false;
+                }
+                b'\n' => stats.lf += 1,
+                // um \r só é um terminador sozinho se não vier um \n depois,
+                // o que só se sabe no próximo byte
+                b'\r' => {
+                    if prev_cr {
+                        stats.cr += 1;
+                    }
+                    prev_cr = true;
+                }
+                _ => {
+                    if prev_cr {
+                        stats.cr += 1;
+                    }
+                    prev_cr = false;
+                }
+            }
+        }
+        let len = chunk.len();
+        file.consume(len);
+    }
+    if prev_cr {
+        stats.cr += 1;
+    }
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{detect, ConvertEol, EolStats, LineEnding};
+    use std::io::{BufReader, Cursor, Read};
+
+    // um buffer de 1 byte força o \r e o \n de um CRLF a chegarem em
+    // leituras diferentes
+    fn convert(input: &[u8], target: Option<LineEnding>, bom: bool) -> Vec<u8> {
+        let reader = BufReader::with_capacity(1, Cursor::new(input.to_vec()));
+        let mut output = Vec::new();
+        ConvertEol::new(reader, target, bom)
+            .read_to_end(&mut output)
+            .unwrap();
+        output
+    }
+
+    #[test]
+    fn test_convert_eol() {
+        let input = b"a\r\nb\rc\nd";
+        assert_eq!(convert(input, Some(LineEnding::Lf), false), b"a\nb\nc\nd");
+        assert_eq!(
+            convert(input, Some(LineEnding::Crlf), false),
+            b"a\r\nb\r\nc\r\nd"
+        );
+        assert_eq!(convert(input, None, false), input);
+        assert_eq!(convert(b"\r\n\r\n", Some(LineEnding::Lf), false), b"\n\n");
+    }
+
+    #[test]
+    fn test_strip_bom() {
+        assert_eq!(convert(b"\xef\xbb\xbfab\n", None, true), b"ab\n");
+        assert_eq!(
+            convert(b"\xef\xbb\xbfab\n", None, false),
+            b"\xef\xbb\xbfab\n"
+        );
+        // o BOM só é removido do começo do arquivo
+        assert_eq!(convert(b"a\xef\xbb\xbf", None, true), b"a\xef\xbb\xbf");
+        assert_eq!(convert(b"a", None, true), b"a");
+    }
+
+    #[test]
+    fn test_detect() {
+        let reader = BufReader::with_capacity(1, Cursor::new(b"a\r\nb\rc\n\r"));
+        let expected = EolStats {
+            lf: 1,
+            crlf: 1,
+            cr: 2,
+            bom: false,
+        };
+        assert_eq!(detect(reader).unwrap(), expected);
+        assert_eq!(expected.to_string(), "mixed (1 LF, 1 CRLF, 2 CR)");
+        assert_eq!(detect(Cursor::new("")).unwrap().to_string(), "none");
+    }
+}
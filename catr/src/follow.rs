@@ -0,0 +1,324 @@
+use super::{open, wrap, Config, LinePrinter, MyResult, BUF_SIZE};
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Seek, SeekFrom};
+use std::time::Duration;
+
+// Opções de --follow (-f) e -F
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FollowOptions {
+    // -F: acompanha o nome do arquivo, reabrindo-o quando ele é renomeado
+    // ou recriado (rotação de logs) e esperando ele aparecer
+    pub reopen: bool,
+    // para de acompanhar quando este processo terminar
+    pub pid: Option<u32>,
+    // intervalo entre as verificações. Com inotify, as mudanças chegam na
+    // hora e o intervalo só vale para --pid e para a checagem de rotação
+    pub sleep_interval: Duration,
+}
+
+// --------------------------------------------------
+// Um arquivo acompanhado. O leitor usa uma cópia do descritor de file, que
+// compartilha a mesma posição, então file serve para descobrir quanto já foi
+// lido e para detectar truncamento e troca do arquivo
+struct Followed {
+    name: String,
+    file: Option<File>,
+    reader: Option<Box<dyn BufRead>>,
+    // o aviso de arquivo inacessível só é mostrado uma vez
+    warned: bool,
+}
+
+impl Followed {
+    fn open(name: &str, config: &Config) -> MyResult<Self> {
+        let mut followed = Followed {
+            name: name.to_string(),
+            file: None,
+            reader: None,
+            warned: false,
+        };
+        followed.reopen(config)?;
+        Ok(followed)
+    }
+
+    // abre o arquivo pelo nome, começando do início
+    fn reopen(&mut self, config: &Config) -> MyResult<()> {
+        let file = File::open(&self.name)?;
+        self.reader = Some(reader(&file, config)?);
+        self.file = Some(file);
+        self.warned = false;
+        Ok(())
+    }
+
+    // o nome passou a apontar para outro arquivo (ou o arquivo apareceu)
+    fn replaced(&mut self) -> bool {
+        match fs::metadata(&self.name) {
+            Ok(meta) => match &self.file {
+                Some(file) => file
+                    .metadata()
+                    .map_or(true, |current| !same_file(&current, &meta)),
+                None => true,
+            },
+            Err(err) => {
+                if !self.warned && self.file.is_some() {
+                    eprintln!(
+                        "catr: {} has become inaccessible: {}",
+                        self.name, err
+                    );
+                    self.warned = true;
+                }
+                false
+            }
+        }
+    }
+
+    // o arquivo ficou menor do que o que já foi lido, então foi truncado e
+    // a leitura recomeça do início
+    fn rewind_if_truncated(&mut self, config: &Config) -> MyResult<()> {
+        let file = match self.file.as_mut() {
+            Some(file) => file,
+            None => return Ok(()),
+        };
+        let len = file.metadata()?.len();
+        if len < file.stream_position()? {
+            eprintln!("catr: {}: file truncated", self.name);
+            file.seek(SeekFrom::Start(0))?;
+            // o leitor antigo pode ter bytes do conteúdo anterior no buffer
+            self.reader = Some(reader(file, config)?);
+        }
+        Ok(())
+    }
+}
+
+// monta a cadeia de leitores sobre uma cópia do descritor
+fn reader(file: &File, config: &Config) -> MyResult<Box<dyn BufRead>> {
+    let file = BufReader::with_capacity(BUF_SIZE, file.try_clone()?);
+    wrap(Box::new(file), config)
+}
+
+// O stdin só é acompanhado quando é um arquivo regular (catr -f < log.txt),
+// pelo descritor. Um pipe ou terminal é lido uma única vez
+#[cfg(unix)]
+fn stdin_file() -> Option<File> {
+    use std::os::fd::AsFd;
+    let fd = io::stdin().as_fd().try_clone_to_owned().ok()?;
+    let file = File::from(fd);
+    file.metadata().ok()?.is_file().then_some(file)
+}
+
+#[cfg(not(unix))]
+fn stdin_file() -> Option<File> {
+    None
+}
+
+// --------------------------------------------------
+// Imprime os arquivos como print_lines e depois continua esperando novos
+// dados no fim de cada um, até o processo de --pid terminar ou o catr ser
+// interrompido. Como no tail do GNU, termina logo depois da leitura quando
+// não sobra nenhum arquivo para acompanhar, e com erro se algum deles não
+// pôde ser aberto
+pub fn follow_files(
+    config: &Config,
+    options: FollowOptions,
+) -> MyResult<usize> {
+    let mut printer = LinePrinter::new(config);
+    let mut watcher = Watcher::new();
+    let mut files = Vec::new();
+    for filename in &config.files {
+        if filename == "-" {
+            if let Some(file) = stdin_file() {
+                let mut followed = Followed {
+                    name: filename.to_string(),
+                    reader: Some(reader(&file, config)?),
+                    file: Some(file),
+                    warned: false,
+                };
+                if let Some(reader) = followed.reader.as_mut() {
+                    printer.print(reader, filename)?;
+                }
+                files.push(followed);
+                continue;
+            }
+            match open(filename, config) {
+                Err(err) => {
+                    eprintln!("Failed to open {}: {}", filename, err);
+                    printer.failures += 1;
+                }
+                Ok(mut file) => {
+                    printer.print(&mut file, filename)?;
+                }
+            }
+            continue;
+        }
+        match Followed::open(filename, config) {
+            Err(err) => {
+                eprintln!("Failed to open {}: {}", filename, err);
+                printer.failures += 1;
+                // com -F, o arquivo pode ser criado depois
+                if options.reopen {
+                    files.push(Followed {
+                        name: filename.to_string(),
+                        file: None,
+                        reader: None,
+                        warned: true,
+                    });
+                }
+            }
+            Ok(mut followed) => {
+                if let Some(reader) = followed.reader.as_mut() {
+                    printer.print(reader, filename)?;
+                }
+                watcher.watch(filename);
+                files.push(followed);
+            }
+        }
+    }
+    printer.flush()?;
+    if files.is_empty() {
+        if printer.failures > 0 {
+            return Err(From::from("no files remaining"));
+        }
+        return printer.finish();
+    }
+
+    loop {
+        // a verificação acontece antes da leitura, assim o que o processo
+        // escreveu antes de terminar ainda é impresso
+        let alive = options.pid.is_none_or(process_exists);
+        for followed in &mut files {
+            // o stdin não tem um nome para ser reaberto
+            let named = followed.name != "-";
+            if options.reopen && named && followed.replaced() {
+                // termina de ler o que foi escrito no arquivo antigo antes
+                // de passar para o novo
+                if let Some(reader) = followed.reader.as_mut() {
+                    printer.print(reader, &followed.name)?;
+                }
+                let appeared = followed.file.is_none();
+                if followed.reopen(config).is_ok() {
+                    eprintln!(
+                        "catr: {} has {}; following new file",
+                        followed.name,
+                        if appeared { "appeared" } else { "been replaced" }
+                    );
+                    watcher.watch(&followed.name);
+                }
+            }
+            followed.rewind_if_truncated(config)?;
+            if let Some(reader) = followed.reader.as_mut() {
+                printer.print(reader, &followed.name)?;
+            }
+        }
+        printer.flush()?;
+        if !alive {
+            break;
+        }
+        watcher.wait(options.sleep_interval);
+    }
+    printer.finish()
+}
+
+// --------------------------------------------------
+// Compara dispositivo e inode, que identificam o arquivo independente do nome
+#[cfg(unix)]
+fn same_file(a: &fs::Metadata, b: &fs::Metadata) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    a.dev() == b.dev() && a.ino() == b.ino()
+}
+
+// sem inode, uma troca do arquivo não é detectada
+#[cfg(not(unix))]
+fn same_file(_a: &fs::Metadata, _b: &fs::Metadata) -> bool {
+    true
+}
+
+// --------------------------------------------------
+// O sinal 0 não é entregue ao processo: kill apenas verifica se ele existe.
+// EPERM indica que o processo existe, mas pertence a outro usuário
+#[cfg(unix)]
+fn process_exists(pid: u32) -> bool {
+    let ret = unsafe { libc::kill(pid as libc::pid_t, 0) };
+    ret == 0 || io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+// sem kill, --pid não tem efeito e o catr segue até ser interrompido
+#[cfg(not(unix))]
+fn process_exists(_pid: u32) -> bool {
+    true
+}
+
+// --------------------------------------------------
+// Espera até um arquivo mudar ou o intervalo acabar. No Linux usa inotify:
+// uma thread fica bloqueada lendo os eventos e avisa pelo canal, assim a
+// espera pode ter um limite de tempo com recv_timeout. Se o inotify não
+// estiver disponível (ou em outros sistemas), apenas dorme pelo intervalo e
+// os arquivos são verificados a cada volta
+enum Watcher {
+    #[cfg(target_os = "linux")]
+    Inotify {
+        watches: inotify::Watches,
+        events: std::sync::mpsc::Receiver<()>,
+    },
+    Poll,
+}
+
+impl Watcher {
+    fn new() -> Self {
+        #[cfg(target_os = "linux")]
+        if let Ok(watcher) = Watcher::inotify() {
+            return watcher;
+        }
+        Watcher::Poll
+    }
+
+    #[cfg(target_os = "linux")]
+    fn inotify() -> io::Result<Self> {
+        let mut inotify = inotify::Inotify::init()?;
+        let watches = inotify.watches();
+        let (sender, events) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            let mut buffer = [0; 4096];
+            // o conteúdo dos eventos não importa, todos os arquivos são
+            // verificados a cada aviso
+            while inotify.read_events_blocking(&mut buffer).is_ok() {
+                if sender.send(()).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Watcher::Inotify { watches, events })
+    }
+
+    fn watch(&mut self, path: &str) {
+        #[cfg(target_os = "linux")]
+        if let Watcher::Inotify { watches, .. } = self {
+            use inotify::WatchMask;
+            let mask = WatchMask::MODIFY
+                | WatchMask::ATTRIB
+                | WatchMask::MOVE_SELF
+                | WatchMask::DELETE_SELF;
+            // se não for possível observar o arquivo, a verificação a cada
+            // intervalo continua valendo
+            let _ = watches.add(path, mask);
+        }
+        #[cfg(not(target_os = "linux"))]
+        let _ = path;
+    }
+
+    fn wait(&self, timeout: Duration) {
+        match self {
+            #[cfg(target_os = "linux")]
+            Watcher::Inotify { events, .. } => {
+                use std::sync::mpsc::RecvTimeoutError;
+                match events.recv_timeout(timeout) {
+                    // junta os avisos acumulados em uma única verificação
+                    Ok(()) => while events.try_recv().is_ok() {},
+                    Err(RecvTimeoutError::Timeout) => {}
+                    Err(RecvTimeoutError::Disconnected) => {
+                        std::thread::sleep(timeout)
+                    }
+                }
+            }
+            Watcher::Poll => std::thread::sleep(timeout),
+        }
+    }
+}
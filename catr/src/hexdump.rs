@@ -0,0 +1,239 @@
+use std::io::{self, BufRead, Read, Write};
+
+// Opções do modo -x, no formato do xxd
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HexOptions {
+    // bytes por linha
+    pub cols: usize,
+    // bytes por grupo de dígitos hexadecimais; 0 desliga o agrupamento
+    pub group: usize,
+    // quantos bytes do início da entrada são pulados
+    pub offset: u64,
+    // quantos bytes são mostrados depois do offset
+    pub length: Option<u64>,
+}
+
+// --------------------------------------------------
+// Recebe os bytes de todos os arquivos, na ordem, e escreve as linhas do
+// dump à medida que cada uma fica completa. Assim a entrada é tratada como
+// um fluxo único, do mesmo jeito que o cat concatena os arquivos, e nada
+// precisa ser guardado além de uma linha
+pub struct HexDumper<W: Write> {
+    out: W,
+    options: HexOptions,
+    // posição, no fluxo de entrada, do próximo byte recebido
+    pos: u64,
+    // bytes já mostrados, para respeitar --length
+    shown: u64,
+    row: Vec<u8>,
+    // offset do primeiro byte de row
+    row_offset: u64,
+}
+
+impl<W: Write> HexDumper<W> {
+    pub fn new(out: W, options: HexOptions) -> Self {
+        HexDumper {
+            out,
+            options,
+            pos: 0,
+            shown: 0,
+            row: Vec::new(),
+            row_offset: options.offset,
+        }
+    }
+
+    // indica se --length já foi atingido, para que quem chama possa parar
+    // de ler a entrada
+    pub fn is_done(&self) -> bool {
+        matches!(self.options.length, Some(length) if self.shown >= length)
+    }
+
+    pub fn write_bytes(&mut self, mut bytes: &[u8]) -> io::Result<()> {
+        // descarta o que vem antes de --offset
+        if self.pos < self.options.offset {
+            let skip = (self.options.offset - self.pos).min(bytes.len() as u64);
+            self.pos += skip;
+            bytes = &bytes[skip as usize..];
+        }
+        for &byte in bytes {
+            if self.is_done() {
+                break;
+            }
+            self.row.push(byte);
+            self.pos += 1;
+            self.shown += 1;
+            if self.row.len() == self.options.cols {
+                self.write_row()?;
+            }
+        }
+        Ok(())
+    }
+
+    // escreve a última linha incompleta
+    pub fn finish(mut self) -> io::Result<()> {
+        if !self.row.is_empty() {
+            self.write_row()?;
+        }
+        self.out.flush()
+    }
+
+    fn write_row(&mut self) -> io::Result<()> {
+        let group = match self.options.group {
+            0 => self.options.cols,
+            group => group,
+        };
+        let mut line = format!("{:08x}: ", self.row_offset);
+        for (i, byte) in self.row.iter().enumerate() {
+            line.push_str(&format!("{:02x}", byte));
+            if (i + 1) % group == 0 {
+                line.push(' ');
+            }
+        }
+        // completa a área hexadecimal para que a coluna ASCII fique
+        // alinhada mesmo na última linha
+        let width = 10 + hex_width(self.options.cols, group);
+        while line.len() < width {
+            line.push(' ');
+        }
+        line.push(' ');
+        line.extend(self.row.iter().map(|&byte| {
+            if (0x20..0x7f).contains(&byte) {
+                byte as char
+            } else {
+                '.'
+            }
+        }));
+        writeln!(self.out, "{}", line)?;
+        self.row_offset += self.row.len() as u64;
+        self.row.clear();
+        Ok(())
+    }
+}
+
+// --------------------------------------------------
+// Largura da área hexadecimal de uma linha completa, incluindo o espaço
+// depois de cada grupo
+fn hex_width(cols: usize, group: usize) -> usize {
+    cols * 2 + cols.div_ceil(group)
+}
+
+// --------------------------------------------------
+// Faz o caminho inverso (-r): lê linhas no formato "offset: hex  ascii" e
+// escreve os bytes correspondentes. pos é a quantidade de bytes já escrita
+// na saída; se o offset de uma linha estiver à frente, a lacuna é preenchida
+// com zeros, como acontece quando o xxd -r escreve em um arquivo
+pub fn reverse(
+    mut input: impl BufRead,
+    out: &mut impl Write,
+    pos: &mut u64,
+) -> io::Result<()> {
+    let mut line = String::new();
+    let mut line_num = 0;
+    loop {
+        line.clear();
+        if input.read_line(&mut line)? == 0 {
+            break;
+        }
+        line_num += 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let (offset, bytes) = parse_line(&line).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid hex dump at line {}", line_num),
+            )
+        })?;
+        if offset < *pos {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("offset goes backwards at line {}", line_num),
+            ));
+        }
+        io::copy(&mut io::repeat(0).take(offset - *pos), out)?;
+        out.write_all(&bytes)?;
+        *pos = offset + bytes.len() as u64;
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+// Separa o offset e os bytes de uma linha do dump. A área hexadecimal
+// termina no primeiro par de espaços, já que entre os grupos existe apenas
+// um; assim a coluna ASCII é ignorada mesmo quando parece hexadecimal
+fn parse_line(line: &str) -> Option<(u64, Vec<u8>)> {
+    let (offset, rest) = line.split_once(':')?;
+    let offset = u64::from_str_radix(offset.trim(), 16).ok()?;
+    let rest = rest.strip_prefix(' ').unwrap_or(rest);
+    let hex = match rest.find("  ") {
+        Some(end) => &rest[..end],
+        None => rest.trim_end(),
+    };
+    let digits: Vec<u8> = hex.bytes().filter(|b| *b != b' ').collect();
+    if !digits.len().is_multiple_of(2) {
+        return None;
+    }
+    let bytes = digits
+        .chunks(2)
+        .map(|pair| {
+            let pair = std::str::from_utf8(pair).ok()?;
+            u8::from_str_radix(pair, 16).ok()
+        })
+        .collect::<Option<Vec<u8>>>()?;
+    Some((offset, bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_line, reverse, HexDumper, HexOptions};
+
+    fn dump(input: &[u8], options: HexOptions) -> String {
+        let mut out = Vec::new();
+        let mut dumper = HexDumper::new(&mut out, options);
+        dumper.write_bytes(input).unwrap();
+        dumper.finish().unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_dump() {
+        let options = HexOptions {
+            cols: 4,
+            group: 3,
+            offset: 0,
+            length: None,
+        };
+        assert_eq!(
+            dump(b"abcdef", options),
+            "00000000: 616263 64  abcd\n00000004: 6566       ef\n"
+        );
+        let options = HexOptions {
+            offset: 1,
+            length: Some(2),
+            ..options
+        };
+        assert_eq!(dump(b"a\x00\nd", options), "00000001: 000a       ..\n");
+    }
+
+    #[test]
+    fn test_parse_line() {
+        assert_eq!(
+            parse_line("00000010: 6162 6364  abcd\n"),
+            Some((16, b"abcd".to_vec()))
+        );
+        // a coluna ASCII "ab" não pode ser lida como hexadecimal
+        assert_eq!(parse_line("00000000: 0a0b  ab\n"), Some((0, vec![10, 11])));
+        assert_eq!(parse_line("00000000: 0a0  ..\n"), None);
+        assert_eq!(parse_line("sem offset\n"), None);
+    }
+
+    #[test]
+    fn test_reverse() {
+        let input = "00000002: 6162  ab\n00000004: 63  c\n";
+        let mut out = Vec::new();
+        let mut pos = 0;
+        reverse(input.as_bytes(), &mut out, &mut pos).unwrap();
+        assert_eq!(out, b"\x00\x00abc");
+        assert_eq!(pos, 5);
+    }
+}
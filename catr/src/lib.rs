@@ -1,11 +1,47 @@
+mod encoding;
+mod eol;
+mod follow;
+mod hexdump;
+mod range;
+
 use clap::{App, Arg};
+use encoding::{DecodeReader, EncodeWriter};
+pub use encoding::Charset;
+use eol::ConvertEol;
+pub use eol::LineEnding;
+pub use follow::FollowOptions;
+use hexdump::HexDumper;
+pub use hexdump::HexOptions;
+pub use range::LineRange;
+use range::RangeFilter;
 use std::error::Error;
 use std::fs::File;
-use std::io::{self, BufRead, BufReader};
+use std::io::{self, BufRead, BufReader, BufWriter, Cursor, Read, Write};
+use std::time::Duration;
 
 // Representa um result que pode conter o struc de configuração ou um erro
 type MyResult<T> = Result<T, Box<dyn Error>>;
 
+// tamanho dos buffers de leitura e escrita
+const BUF_SIZE: usize = 64 * 1024;
+// quantidade de bytes necessária para reconhecer o maior magic number (xz)
+const MAGIC_LEN: usize = 6;
+// opções que não fazem sentido com uma entrada que continua crescendo
+const FOLLOW_CONFLICTS: &[&str] = &[
+    "decompress",
+    "detect",
+    "hexdump",
+    "reverse",
+    "range",
+    "from_encoding",
+    "number_per_file",
+];
+// maior largura aceita em --number-width; larguras maiores fariam o format!
+// entrar em pânico
+const MAX_NUMBER_WIDTH: u64 = 1024;
+// maior valor de --cols, o mesmo limite do xxd
+const MAX_COLS: u64 = 256;
+
 // Config representa os argumentos que serão passados para o comando
 // tanto posicionais (files) quando opcionais (flags)
 // macro derive(Debug) implementa a trait debug automaticamente quando os campos
@@ -15,6 +51,91 @@ pub struct Config {
     files: Vec<String>,
     number_lines: bool,
     number_nonblank_lines: bool,
+    show_nonprinting: bool,
+    show_ends: bool,
+    show_tabs: bool,
+    squeeze_blank: bool,
+    decompress: Option<Decompress>,
+    line_ending: Option<LineEnding>,
+    strip_bom: bool,
+    detect_eol: bool,
+    hexdump: Option<HexOptions>,
+    reverse_hexdump: bool,
+    from_encoding: Option<Charset>,
+    to_encoding: Option<Charset>,
+    strict_encoding: bool,
+    numbering: Numbering,
+    range: Option<LineRange>,
+    follow: Option<FollowOptions>,
+}
+
+// Formato dos números de linha de -n e -b, no estilo do nl. O padrão
+// reproduz o cat: largura 6, alinhado à direita e seguido de um tab
+#[derive(Debug, Clone, PartialEq)]
+pub struct Numbering {
+    width: usize,
+    separator: String,
+    start: i64,
+    increment: i64,
+    align: NumberAlign,
+    // recomeça a contagem em cada arquivo
+    per_file: bool,
+}
+
+// Valores de --number-format, com os mesmos nomes do nl
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NumberAlign {
+    Left,      // ln
+    Right,     // rn
+    RightZero, // rz
+}
+
+impl Numbering {
+    fn format(&self, num: i64) -> String {
+        let width = self.width;
+        match self.align {
+            NumberAlign::Left => format!("{:<width$}", num),
+            NumberAlign::Right => format!("{:>width$}", num),
+            NumberAlign::RightZero => format!("{:0width$}", num),
+        }
+    }
+}
+
+// Formatos aceitos por --decompress. Auto escolhe o formato pelos primeiros
+// bytes do arquivo (os "magic bytes") e deixa passar sem alteração o que não
+// estiver compactado, como o zcat -f
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Decompress {
+    Auto,
+    Gzip,
+    Xz,
+    Zstd,
+    Bzip2,
+}
+
+impl Config {
+    // nenhuma opção altera o conteúdo, então a saída é idêntica à entrada
+    fn is_plain(&self) -> bool {
+        !(self.number_lines
+            || self.number_nonblank_lines
+            || self.show_nonprinting
+            || self.show_ends
+            || self.show_tabs
+            || self.squeeze_blank
+            || self.to_encoding.is_some()
+            || self.range.is_some()
+            || self.follow.is_some()
+            || self.transforms_input())
+    }
+
+    // a entrada precisa passar por um leitor que altera os bytes antes de
+    // qualquer outra opção
+    fn transforms_input(&self) -> bool {
+        self.decompress.is_some()
+            || self.from_encoding.is_some()
+            || self.line_ending.is_some()
+            || self.strip_bom
+    }
 }
 
 // --------------------------------------------------
@@ -45,58 +166,956 @@ pub fn get_args() -> MyResult<Config> {
                 .help("Number non-blank lines")
                 .takes_value(false),
         )
+        .arg(
+            Arg::with_name("number_width")
+                .long("number-width")
+                .value_name("WIDTH")
+                .help("Width of line numbers [default: 6]"),
+        )
+        .arg(
+            Arg::with_name("number_separator")
+                .long("number-separator")
+                .value_name("STRING")
+                .help("Text between line number and line [default: TAB]"),
+        )
+        .arg(
+            Arg::with_name("number_start")
+                .long("number-start")
+                .value_name("NUMBER")
+                .allow_hyphen_values(true)
+                .help("First line number [default: 1]"),
+        )
+        .arg(
+            Arg::with_name("number_increment")
+                .long("number-increment")
+                .value_name("NUMBER")
+                .allow_hyphen_values(true)
+                .help("Line number increment [default: 1]"),
+        )
+        .arg(
+            Arg::with_name("number_format")
+                .long("number-format")
+                .value_name("FORMAT")
+                .help("Line number alignment: ln, rn or rz [default: rn]")
+                .possible_values(&["ln", "rn", "rz"]),
+        )
+        .arg(
+            Arg::with_name("number_per_file")
+                .long("number-per-file")
+                .help("Restart line numbers at each file")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("range")
+                .long("range")
+                .value_name("START:END")
+                .help("Print only lines START to END (negative: from the end)")
+                // permite intervalos relativos ao fim, como -30:
+                .allow_hyphen_values(true)
+                .conflicts_with_all(&["hexdump", "reverse", "detect"]),
+        )
+        .arg(
+            Arg::with_name("follow")
+                .short("f")
+                .long("follow")
+                .help("Keep reading data appended to the files")
+                .takes_value(false)
+                .conflicts_with_all(FOLLOW_CONFLICTS),
+        )
+        .arg(
+            Arg::with_name("follow_name")
+                .short("F")
+                .help("Like --follow, reopening files that are renamed or \
+                       truncated")
+                .takes_value(false)
+                .conflicts_with_all(FOLLOW_CONFLICTS),
+        )
+        .arg(
+            Arg::with_name("pid")
+                .long("pid")
+                .value_name("PID")
+                .help("With --follow, stop after process PID exits"),
+        )
+        .arg(
+            Arg::with_name("sleep_interval")
+                .long("sleep-interval")
+                .value_name("SECONDS")
+                .help("With --follow, seconds between checks [default: 1]"),
+        )
+        .arg(
+            Arg::with_name("show_all")
+                .short("A")
+                .long("show-all")
+                .help("Equivalent to -vET")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("show_nonprinting_ends")
+                .short("e")
+                .help("Equivalent to -vE")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("show_ends")
+                .short("E")
+                .long("show-ends")
+                .help("Display $ at end of each line")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("show_nonprinting_tabs")
+                .short("t")
+                .help("Equivalent to -vT")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("squeeze_blank")
+                .short("s")
+                .long("squeeze-blank")
+                .help("Suppress repeated empty output lines")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("decompress")
+                .short("z")
+                .long("decompress")
+                .value_name("FORMAT")
+                .help("Decompress input (format detected when omitted)")
+                // o valor é opcional e precisa do "=" para não ser
+                // confundido com o nome de um arquivo: --decompress=gzip
+                .min_values(0)
+                .require_equals(true)
+                .possible_values(&["auto", "gzip", "xz", "zstd", "bzip2"]),
+        )
+        .arg(
+            Arg::with_name("to_lf")
+                .long("to-lf")
+                .help("Convert line endings to LF")
+                .takes_value(false)
+                .conflicts_with("to_crlf"),
+        )
+        .arg(
+            Arg::with_name("to_crlf")
+                .long("to-crlf")
+                .help("Convert line endings to CRLF")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("strip_bom")
+                .long("strip-bom")
+                .help("Remove a UTF-8 byte order mark from each file")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("detect")
+                .long("detect")
+                .help("Report the line endings used by each file")
+                .takes_value(false)
+                .conflicts_with_all(&["to_lf", "to_crlf", "strip_bom"]),
+        )
+        .arg(
+            Arg::with_name("hexdump")
+                .short("x")
+                .long("hexdump")
+                .help("Display input as a hex dump, like xxd")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("cols")
+                .long("cols")
+                .value_name("COLS")
+                .help("Bytes per hex dump line [default: 16]")
+                .requires("hexdump"),
+        )
+        .arg(
+            Arg::with_name("group")
+                .long("group")
+                .value_name("BYTES")
+                .help("Bytes per hex dump group, 0 disables [default: 2]")
+                .requires("hexdump"),
+        )
+        .arg(
+            Arg::with_name("offset")
+                .long("offset")
+                .value_name("OFFSET")
+                .help("Skip OFFSET bytes before the hex dump")
+                .requires("hexdump"),
+        )
+        .arg(
+            Arg::with_name("length")
+                .long("length")
+                .value_name("LENGTH")
+                .help("Stop the hex dump after LENGTH bytes")
+                .requires("hexdump"),
+        )
+        .arg(
+            Arg::with_name("reverse")
+                .short("r")
+                .long("reverse")
+                .help("Convert a hex dump back into bytes")
+                .takes_value(false)
+                .conflicts_with("hexdump"),
+        )
+        .arg(
+            Arg::with_name("from_encoding")
+                .long("from-encoding")
+                .value_name("ENCODING")
+                .help("Character encoding of the input (e.g. latin1, utf-16)"),
+        )
+        .arg(
+            Arg::with_name("to_encoding")
+                .long("to-encoding")
+                .value_name("ENCODING")
+                .help("Character encoding of the output")
+                .conflicts_with_all(&["hexdump", "reverse", "detect"]),
+        )
+        .arg(
+            Arg::with_name("strict")
+                .long("strict")
+                .help("Fail on invalid or unconvertible characters")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("show_tabs")
+                .short("T")
+                .long("show-tabs")
+                .help("Display TAB characters as ^I")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("show_nonprinting")
+                .short("v")
+                .long("show-nonprinting")
+                .help("Use ^ and M- notation, except for LFD and TAB")
+                .takes_value(false),
+        )
         .get_matches();
 
+    // as flags combinadas (-A, -e e -t) apenas ligam as flags individuais
+    let show_all = matches.is_present("show_all");
+    let show_e = matches.is_present("show_nonprinting_ends");
+    let show_t = matches.is_present("show_nonprinting_tabs");
+
+    // não usamos default_value em --cols e --group porque o clap passaria
+    // a exigir -x mesmo quando elas não são informadas
+    let cols = matches
+        .value_of("cols")
+        .map(parse_int)
+        .transpose()
+        .map_err(|e| format!("illegal column count -- {}", e))?;
+    let group = matches
+        .value_of("group")
+        .map(parse_int)
+        .transpose()
+        .map_err(|e| format!("illegal group size -- {}", e))?;
+    let offset = matches
+        .value_of("offset")
+        .map(parse_int)
+        .transpose()
+        .map_err(|e| format!("illegal offset -- {}", e))?;
+    let length = matches
+        .value_of("length")
+        .map(parse_int)
+        .transpose()
+        .map_err(|e| format!("illegal length -- {}", e))?;
+    let number_width = matches
+        .value_of("number_width")
+        .map(parse_int)
+        .transpose()
+        .map_err(|e| format!("illegal line number width -- {}", e))?;
+    let number_start = matches
+        .value_of("number_start")
+        .map(parse_signed)
+        .transpose()
+        .map_err(|e| format!("illegal starting line number -- {}", e))?;
+    let number_increment = matches
+        .value_of("number_increment")
+        .map(parse_signed)
+        .transpose()
+        .map_err(|e| format!("illegal line number increment -- {}", e))?;
+    let numbering = Numbering {
+        width: match number_width {
+            Some(width) if width == 0 || width > MAX_NUMBER_WIDTH => {
+                return Err(From::from(format!(
+                    "illegal line number width -- {}",
+                    width
+                )))
+            }
+            width => width.unwrap_or(6) as usize,
+        },
+        separator: matches
+            .value_of("number_separator")
+            .unwrap_or("\t")
+            .to_string(),
+        start: number_start.unwrap_or(1),
+        increment: number_increment.unwrap_or(1),
+        align: match matches.value_of("number_format") {
+            Some("ln") => NumberAlign::Left,
+            Some("rz") => NumberAlign::RightZero,
+            _ => NumberAlign::Right,
+        },
+        per_file: matches.is_present("number_per_file"),
+    };
+    // qualquer opção de formato sem -b liga a numeração de todas as
+    // linhas, do mesmo jeito que o nl numera sem precisar de outra flag
+    let number_options = [
+        "number_width",
+        "number_separator",
+        "number_start",
+        "number_increment",
+        "number_format",
+        "number_per_file",
+    ];
+    let number_lines = matches.is_present("number")
+        || (!matches.is_present("number_nonblank")
+            && number_options.iter().any(|opt| matches.is_present(opt)));
+
+    let range = matches
+        .value_of("range")
+        .map(|val| {
+            range::parse_range(val)
+                .ok_or_else(|| format!("illegal line range -- {}", val))
+        })
+        .transpose()?;
+
+    let follow =
+        matches.is_present("follow") || matches.is_present("follow_name");
+    let pid = matches
+        .value_of("pid")
+        .map(parse_int)
+        .transpose()
+        .map_err(|e| format!("illegal process id -- {}", e))?;
+    let sleep_interval = matches
+        .value_of("sleep_interval")
+        .map(|val| {
+            // aceita frações de segundo, como o tail: --sleep-interval 0.5
+            val.parse::<f64>()
+                .ok()
+                .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+                .ok_or_else(|| format!("illegal sleep interval -- {}", val))
+        })
+        .transpose()?;
+    // o clap 2 não tem como exigir -f ou -F, então a checagem é feita aqui
+    if !follow && (pid.is_some() || sleep_interval.is_some()) {
+        return Err(From::from(
+            "--pid and --sleep-interval require --follow or -F",
+        ));
+    }
+
+    let from_encoding = matches
+        .value_of("from_encoding")
+        .map(parse_encoding)
+        .transpose()?;
+    let to_encoding = matches
+        .value_of("to_encoding")
+        .map(parse_encoding)
+        .transpose()?;
+
+    let cols = match cols {
+        Some(cols) if cols == 0 || cols > MAX_COLS => {
+            return Err(From::from(format!("illegal column count -- {}", cols)))
+        }
+        cols => cols.unwrap_or(16) as usize,
+    };
+
     // Cria e retorna o config com os valores que foram parseados pelo Clap
     Ok(Config {
         // como files possui um valor padrão, não corre o risco de panic
         files: matches.values_of_lossy("files").unwrap(),
-        number_lines: matches.is_present("number"),
+        number_lines,
         number_nonblank_lines: matches.is_present("number_nonblank"),
+        show_nonprinting: show_all
+            || show_e
+            || show_t
+            || matches.is_present("show_nonprinting"),
+        show_ends: show_all || show_e || matches.is_present("show_ends"),
+        show_tabs: show_all || show_t || matches.is_present("show_tabs"),
+        squeeze_blank: matches.is_present("squeeze_blank"),
+        decompress: if matches.is_present("decompress") {
+            // possible_values garante que o valor é um dos conhecidos
+            Some(match matches.value_of("decompress") {
+                Some("gzip") => Decompress::Gzip,
+                Some("xz") => Decompress::Xz,
+                Some("zstd") => Decompress::Zstd,
+                Some("bzip2") => Decompress::Bzip2,
+                _ => Decompress::Auto,
+            })
+        } else {
+            None
+        },
+        line_ending: if matches.is_present("to_lf") {
+            Some(LineEnding::Lf)
+        } else if matches.is_present("to_crlf") {
+            Some(LineEnding::Crlf)
+        } else {
+            None
+        },
+        strip_bom: matches.is_present("strip_bom"),
+        detect_eol: matches.is_present("detect"),
+        hexdump: if matches.is_present("hexdump") {
+            Some(HexOptions {
+                cols,
+                group: group.unwrap_or(2) as usize,
+                offset: offset.unwrap_or(0),
+                length,
+            })
+        } else {
+            None
+        },
+        reverse_hexdump: matches.is_present("reverse"),
+        from_encoding,
+        to_encoding,
+        strict_encoding: matches.is_present("strict"),
+        numbering,
+        range,
+        follow: if follow {
+            Some(FollowOptions {
+                reopen: matches.is_present("follow_name"),
+                pid: pid.map(|pid| pid as u32),
+                sleep_interval: sleep_interval
+                    .unwrap_or(Duration::from_secs(1)),
+            })
+        } else {
+            None
+        },
     })
 }
 
 // --------------------------------------------------
 pub fn run(config: Config) -> MyResult<()> {
-    let mut line_num = 0;
-    for filename in config.files {
-        match open(&filename) {
-            Err(err) => eprintln!("Failed to open {}: {}", filename, err),
-            Ok(file) => {
-                // operações com filehandles retornam Result, pois se tratando
-                // de IO, podem falhar
-                for line in file.lines() {
-                    let line = line?;
-                    if config.number_lines {
-                        line_num += 1;
-                        println!("{:>6}\t{}", line_num, line);
-                    } else if config.number_nonblank_lines {
-                        if !line.is_empty() {
-                            line_num += 1;
-                            println!("{:>6}\t{}", line_num, line);
-                        } else {
-                            println!();
-                        }
-                    } else {
-                        println!("{}", line);
+    // os arquivos com problema são pulados, mas contados para que o
+    // programa termine com erro no final, como o cat faz
+    let failures = if config.detect_eol {
+        detect_files(&config)?
+    } else if let Some(options) = config.hexdump {
+        hexdump_files(&config, options)?
+    } else if config.reverse_hexdump {
+        reverse_files(&config)?
+    } else if let Some(options) = config.follow {
+        follow::follow_files(&config, options)?
+    } else if config.is_plain() {
+        copy_files(&config)?
+    } else {
+        print_lines(&config)?
+    };
+
+    if failures > 0 {
+        return Err(From::from(format!(
+            "{} of {} file(s) could not be read",
+            failures,
+            config.files.len()
+        )));
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+// Imprime os arquivos linha a linha aplicando as opções de numeração e
+// visualização. Retorna quantos arquivos não puderam ser lidos; erros de
+// escrita no stdout interrompem o programa
+fn print_lines(config: &Config) -> MyResult<usize> {
+    let mut printer = LinePrinter::new(config);
+    for filename in &config.files {
+        match open(filename, config) {
+            Err(err) => {
+                eprintln!("Failed to open {}: {}", filename, err);
+                printer.failures += 1;
+            }
+            Ok(mut file) => {
+                printer.start_file();
+                // o fim do intervalo já passou, então não há motivo para ler
+                // o restante da entrada
+                if !printer.print(&mut file, filename)? {
+                    break;
+                }
+            }
+        }
+    }
+    printer.finish()
+}
+
+// --------------------------------------------------
+// Estado da impressão linha a linha. Fica em um struct, e não em variáveis
+// locais de print_lines, porque o --follow continua imprimindo com a mesma
+// numeração depois que os arquivos chegam ao fim
+struct LinePrinter<'a> {
+    config: &'a Config,
+    out: Box<dyn Write>,
+    failures: usize,
+    line_num: i64,
+    // indica se o próximo byte escrito começa uma linha nova. É mantido entre
+    // arquivos pois, assim como no cat, um arquivo que não termina com \n
+    // continua na mesma linha do arquivo seguinte
+    at_line_start: bool,
+    // usado pelo -s para saber se a última linha emitida era vazia; também
+    // atravessa arquivos, então uma sequência de linhas vazias que começa
+    // em um arquivo e continua no próximo vira uma só
+    prev_blank: bool,
+    // com --range as linhas passam pelo filtro já formatadas, então a
+    // numeração continua contando as linhas que ficam de fora e mostra a
+    // posição original de cada uma
+    filter: Option<RangeFilter>,
+    // posição, no fluxo concatenado, da linha sendo escrita
+    line_pos: u64,
+    // a linha é lida como bytes e não como String, assim bytes que não são
+    // utf8 válido podem ser exibidos com -v
+    line: Vec<u8>,
+    rendered: Vec<u8>,
+}
+
+impl<'a> LinePrinter<'a> {
+    fn new(config: &'a Config) -> Self {
+        // a saída passa por um BufWriter para não fazer uma chamada de
+        // sistema a cada linha escrita
+        let buffered = BufWriter::with_capacity(BUF_SIZE, io::stdout().lock());
+        // a conversão para outra codificação acontece na saída, depois da
+        // numeração e das marcas de -v, -E e -T
+        let out: Box<dyn Write> = match config.to_encoding {
+            Some(charset) => Box::new(EncodeWriter::new(
+                buffered,
+                charset,
+                config.strict_encoding,
+            )),
+            None => Box::new(buffered),
+        };
+        LinePrinter {
+            config,
+            out,
+            failures: 0,
+            line_num: config.numbering.start,
+            at_line_start: true,
+            prev_blank: false,
+            filter: config.range.map(RangeFilter::new),
+            line_pos: 0,
+            line: Vec::new(),
+            rendered: Vec::new(),
+        }
+    }
+
+    fn start_file(&mut self) {
+        if self.config.numbering.per_file {
+            self.line_num = self.config.numbering.start;
+        }
+    }
+
+    // Lê o arquivo até o fim. Retorna false quando --range já terminou e
+    // nada mais precisa ser lido
+    fn print(
+        &mut self,
+        file: &mut dyn BufRead,
+        filename: &str,
+    ) -> MyResult<bool> {
+        let config = self.config;
+        let numbering = &config.numbering;
+        loop {
+            self.line.clear();
+            // read_until mantém o delimitador no buffer. Um erro de leitura
+            // (um diretório, por exemplo) abandona apenas o arquivo atual,
+            // mas o que foi lido antes dele ainda é impresso
+            let failed = match file.read_until(b'\n', &mut self.line) {
+                Ok(_) => false,
+                Err(err) => {
+                    eprintln!("Failed to read {}: {}", filename, err);
+                    self.failures += 1;
+                    true
+                }
+            };
+            if self.line.is_empty() {
+                break;
+            }
+            if self.at_line_start {
+                self.line_pos += 1;
+                if let Some(filter) = &self.filter {
+                    if filter.is_past(self.line_pos) {
+                        return Ok(false);
+                    }
+                }
+                let is_blank = self.line == b"\n";
+                // a linha descartada não chega a ser numerada
+                if is_blank && self.prev_blank && config.squeeze_blank {
+                    continue;
+                }
+                self.prev_blank = is_blank;
+                if config.number_lines
+                    || (config.number_nonblank_lines && !is_blank)
+                {
+                    write!(
+                        self.rendered,
+                        "{}{}",
+                        numbering.format(self.line_num),
+                        numbering.separator
+                    )?;
+                    self.line_num += numbering.increment;
+                }
+            }
+            write_line(&mut self.rendered, &self.line, config)?;
+            match self.filter.as_mut() {
+                Some(filter) => {
+                    filter.push(self.line_pos, &self.rendered, &mut self.out)?
+                }
+                None => self.out.write_all(&self.rendered)?,
+            }
+            self.rendered.clear();
+            self.at_line_start = self.line.ends_with(b"\n");
+            if failed {
+                break;
+            }
+        }
+        Ok(true)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+
+    // escreve o que ainda estiver guardado por --range e retorna quantos
+    // arquivos não puderam ser lidos
+    fn finish(mut self) -> MyResult<usize> {
+        if let Some(filter) = self.filter.take() {
+            filter.finish(self.line_pos, &mut self.out)?;
+        }
+        self.out.flush()?;
+        Ok(self.failures)
+    }
+}
+
+// --------------------------------------------------
+// Sem nenhuma opção de formatação não há motivo para separar as linhas: os
+// bytes são copiados exatamente como estão, o que funciona para qualquer
+// arquivo (imagens, arquivos compactados, texto com CRLF). io::copy recebe
+// File/Stdin e Stdout concretos, e não um Box<dyn BufRead>, para que no
+// Linux a biblioteca padrão possa usar copy_file_range/splice e evitar
+// copiar os dados para o espaço do usuário
+fn copy_files(config: &Config) -> MyResult<usize> {
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    let mut failures = 0;
+    for filename in &config.files {
+        let copied = match filename.as_str() {
+            // o decodificador e a conversão de finais de linha exigem um
+            // leitor genérico, então a cópia passa pelo Box<dyn BufRead>
+            _ if config.transforms_input() => {
+                match open(filename, config) {
+                    Err(err) => {
+                        eprintln!("Failed to open {}: {}", filename, err);
+                        failures += 1;
+                        continue;
                     }
+                    Ok(mut file) => io::copy(&mut file, &mut out),
                 }
             }
+            "-" => io::copy(&mut io::stdin().lock(), &mut out),
+            _ => match File::open(filename) {
+                Err(err) => {
+                    eprintln!("Failed to open {}: {}", filename, err);
+                    failures += 1;
+                    continue;
+                }
+                Ok(mut file) => io::copy(&mut file, &mut out),
+            },
+        };
+        // io::copy não informa se o erro veio da leitura ou da escrita. Um
+        // pipe fechado do lado de quem lê a saída encerra o programa; os
+        // demais erros são atribuídos ao arquivo
+        match copied {
+            Err(err) if err.kind() == io::ErrorKind::BrokenPipe => {
+                return Err(From::from(err));
+            }
+            Err(err) => {
+                eprintln!("Failed to read {}: {}", filename, err);
+                failures += 1;
+            }
+            Ok(_) => {}
         }
     }
+    out.flush()?;
+    Ok(failures)
+}
+
+// --------------------------------------------------
+// Mostra o tipo de final de linha de cada arquivo em vez do seu conteúdo
+fn detect_files(config: &Config) -> MyResult<usize> {
+    let mut failures = 0;
+    for filename in &config.files {
+        match open(filename, config) {
+            Err(err) => {
+                eprintln!("Failed to open {}: {}", filename, err);
+                failures += 1;
+            }
+            Ok(file) => match eol::detect(file) {
+                Ok(stats) => println!("{}: {}", filename, stats),
+                Err(err) => {
+                    eprintln!("Failed to read {}: {}", filename, err);
+                    failures += 1;
+                }
+            },
+        }
+    }
+    Ok(failures)
+}
+
+// --------------------------------------------------
+// Mostra os arquivos, concatenados, como um dump hexadecimal
+fn hexdump_files(config: &Config, options: HexOptions) -> MyResult<usize> {
+    let stdout = io::stdout();
+    let out = BufWriter::with_capacity(BUF_SIZE, stdout.lock());
+    let mut dumper = HexDumper::new(out, options);
+    let mut failures = 0;
+    for filename in &config.files {
+        if dumper.is_done() {
+            break;
+        }
+        match open(filename, config) {
+            Err(err) => {
+                eprintln!("Failed to open {}: {}", filename, err);
+                failures += 1;
+            }
+            Ok(mut file) => loop {
+                // lê o que já estiver no buffer do arquivo, sem copiar
+                let len = match file.fill_buf() {
+                    Ok([]) => break,
+                    Ok(buffer) => {
+                        dumper.write_bytes(buffer)?;
+                        buffer.len()
+                    }
+                    Err(err) => {
+                        eprintln!("Failed to read {}: {}", filename, err);
+                        failures += 1;
+                        break;
+                    }
+                };
+                file.consume(len);
+                if dumper.is_done() {
+                    break;
+                }
+            },
+        }
+    }
+    dumper.finish()?;
+    Ok(failures)
+}
+
+// --------------------------------------------------
+// Converte dumps hexadecimais de volta em bytes. Os offsets continuam de um
+// arquivo para o outro, então um dump dividido em partes é remontado
+fn reverse_files(config: &Config) -> MyResult<usize> {
+    let stdout = io::stdout();
+    let mut out = BufWriter::with_capacity(BUF_SIZE, stdout.lock());
+    let mut failures = 0;
+    let mut pos = 0;
+    for filename in &config.files {
+        match open(filename, config) {
+            Err(err) => {
+                eprintln!("Failed to open {}: {}", filename, err);
+                failures += 1;
+            }
+            Ok(file) => {
+                if let Err(err) = hexdump::reverse(file, &mut out, &mut pos) {
+                    eprintln!("Failed to read {}: {}", filename, err);
+                    failures += 1;
+                }
+            }
+        }
+    }
+    out.flush()?;
+    Ok(failures)
+}
+
+// --------------------------------------------------
+// Escreve uma linha (possivelmente com o \n no final) aplicando as opções
+// de visualização -v, -E e -T
+fn write_line(
+    out: &mut impl Write,
+    line: &[u8],
+    config: &Config,
+) -> io::Result<()> {
+    let (content, newline) = match line.strip_suffix(b"\n") {
+        Some(content) => (content, true),
+        None => (line, false),
+    };
+    // com -E o \r de um final CRLF fica visível como ^M antes do $, do
+    // mesmo jeito que no cat do GNU
+    let (content, carriage_return) = match content.strip_suffix(b"\r") {
+        Some(content) if newline && config.show_ends => (content, true),
+        _ => (content, false),
+    };
+
+    if config.show_nonprinting || config.show_tabs {
+        let mut buf = Vec::with_capacity(content.len() * 2);
+        for &byte in content {
+            push_visible(&mut buf, byte, config);
+        }
+        out.write_all(&buf)?;
+    } else {
+        out.write_all(content)?;
+    }
+
+    if carriage_return {
+        out.write_all(b"^M")?;
+    }
+    if newline {
+        if config.show_ends {
+            out.write_all(b"$")?;
+        }
+        out.write_all(b"\n")?;
+    }
     Ok(())
 }
 
+// --------------------------------------------------
+// Converte um byte para a notação usada pelo cat: ^X para caracteres de
+// controle, ^? para DEL e o prefixo M- para bytes com o bit mais alto ligado
+fn push_visible(buf: &mut Vec<u8>, byte: u8, config: &Config) {
+    if byte == b'\t' {
+        if config.show_tabs {
+            buf.extend_from_slice(b"^I");
+        } else {
+            buf.push(byte);
+        }
+        return;
+    }
+    if !config.show_nonprinting {
+        buf.push(byte);
+        return;
+    }
+    let mut byte = byte;
+    if byte >= 128 {
+        buf.extend_from_slice(b"M-");
+        byte -= 128;
+    }
+    match byte {
+        0..=31 => buf.extend_from_slice(&[b'^', byte + 64]),
+        127 => buf.extend_from_slice(b"^?"),
+        _ => buf.push(byte),
+    }
+}
+
 // --------------------------------------------------
 // File::open retorna um filehandle, que é um mecanismo para ler conteúdo
 // de arquivos. O filehandle implementa a trai BufRead, que possui a 
 // função BufRead::lines que permite ler os arquivos por linhas de 
 // forma que não corremos o risco de lotar a memório com o conteúdo do
 // arquivo. io::stdin também implementa BufRead
-fn open(filename: &str) -> MyResult<Box<dyn BufRead>> {
-    match filename {
-        "-" => Ok(Box::new(BufReader::new(io::stdin()))),
+// O buffer maior que o padrão (8 KiB) reduz o número de leituras em
+// arquivos grandes
+fn open(filename: &str, config: &Config) -> MyResult<Box<dyn BufRead>> {
+    let file: Box<dyn BufRead> = match filename {
+        "-" => Box::new(BufReader::with_capacity(BUF_SIZE, io::stdin())),
         // ? propaga o erro caso não consiga abrir
-        _ => Ok(Box::new(BufReader::new(File::open(filename)?))),
+        _ => Box::new(BufReader::with_capacity(
+            BUF_SIZE,
+            File::open(filename)?,
+        )),
+    };
+    wrap(file, config)
+}
+
+// --------------------------------------------------
+// Aplica à entrada os leitores que alteram os bytes: descompactação,
+// decodificação e conversão dos finais de linha
+fn wrap(file: Box<dyn BufRead>, config: &Config) -> MyResult<Box<dyn BufRead>> {
+    let file = match config.decompress {
+        None => file,
+        Some(format) => decoder(file, format)?,
+    };
+    // daqui em diante a entrada é sempre utf8
+    let file: Box<dyn BufRead> = match config.from_encoding {
+        None => file,
+        Some(charset) => Box::new(BufReader::with_capacity(
+            BUF_SIZE,
+            DecodeReader::new(file, charset, config.strict_encoding),
+        )),
+    };
+    // a conversão dos finais de linha vem depois da descompactação, já que
+    // precisa enxergar o texto
+    if config.line_ending.is_some() || config.strip_bom {
+        return Ok(Box::new(BufReader::with_capacity(
+            BUF_SIZE,
+            ConvertEol::new(file, config.line_ending, config.strip_bom),
+        )));
+    }
+    Ok(file)
+}
+
+// --------------------------------------------------
+// Envolve o arquivo no decodificador do formato escolhido. Como o resultado
+// continua sendo um Box<dyn BufRead>, o restante do programa (numeração,
+// -v, -s...) não precisa saber que a entrada estava compactada
+fn decoder(
+    mut file: Box<dyn BufRead>,
+    format: Decompress,
+) -> io::Result<Box<dyn BufRead>> {
+    let format = match format {
+        Decompress::Auto => {
+            // lê o começo do arquivo para descobrir o formato e depois
+            // devolve esses bytes na frente do restante com chain, assim
+            // funciona até com o stdin, que não permite voltar atrás
+            let mut magic = Vec::with_capacity(MAGIC_LEN);
+            file.by_ref().take(MAGIC_LEN as u64).read_to_end(&mut magic)?;
+            let detected = detect_compression(&magic);
+            file = Box::new(Cursor::new(magic).chain(file));
+            match detected {
+                Some(format) => format,
+                None => return Ok(file),
+            }
+        }
+        format => format,
+    };
+    // as versões "multi" continuam lendo quando há vários fluxos
+    // compactados concatenados, como faz o zcat
+    Ok(match format {
+        Decompress::Gzip => Box::new(BufReader::new(
+            flate2::bufread::MultiGzDecoder::new(file),
+        )),
+        Decompress::Xz => Box::new(BufReader::new(
+            xz2::bufread::XzDecoder::new_multi_decoder(file),
+        )),
+        Decompress::Zstd => {
+            Box::new(BufReader::new(zstd::Decoder::with_buffer(file)?))
+        }
+        Decompress::Bzip2 => Box::new(BufReader::new(
+            bzip2::bufread::MultiBzDecoder::new(file),
+        )),
+        Decompress::Auto => file,
+    })
+}
+
+// --------------------------------------------------
+// Converte o valor de uma opção numérica, aceitando decimal ou hexadecimal
+// com o prefixo 0x, como o xxd
+fn parse_int(val: &str) -> MyResult<u64> {
+    let parsed = match val.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => val.parse(),
+    };
+    // From converte val (&str) em um erro, como no headr
+    parsed.map_err(|_| From::from(val))
+}
+
+// --------------------------------------------------
+// Como parse_int, mas aceitando números negativos
+fn parse_signed(val: &str) -> MyResult<i64> {
+    val.parse().map_err(|_| From::from(val))
+}
+
+// --------------------------------------------------
+fn parse_encoding(val: &str) -> MyResult<Charset> {
+    encoding::parse_charset(val)
+        .ok_or_else(|| From::from(format!("unknown encoding -- {}", val)))
+}
+
+// --------------------------------------------------
+// Identifica o formato pelos magic bytes do início do arquivo
+fn detect_compression(magic: &[u8]) -> Option<Decompress> {
+    if magic.starts_with(&[0x1f, 0x8b]) {
+        Some(Decompress::Gzip)
+    } else if magic.starts_with(&[0xfd, b'7', b'z', b'X', b'Z', 0x00]) {
+        Some(Decompress::Xz)
+    } else if magic.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+        Some(Decompress::Zstd)
+    } else if magic.starts_with(b"BZh") {
+        Some(Decompress::Bzip2)
+    } else {
+        None
     }
 }
@@ -0,0 +1,183 @@
+use std::collections::VecDeque;
+use std::io::{self, Write};
+
+// Intervalo de --range, com posições começando em 1 e incluindo as duas
+// pontas. Valores negativos contam a partir do fim: -1 é a última linha
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LineRange {
+    start: i64,
+    end: Option<i64>,
+}
+
+// --------------------------------------------------
+// Aceita START:END, START:, :END e valores negativos como -30:
+pub fn parse_range(val: &str) -> Option<LineRange> {
+    let (start, end) = val.split_once(':')?;
+    let parse = |pos: &str| -> Option<Option<i64>> {
+        match pos {
+            "" => Some(None),
+            // a linha 0 não existe
+            pos => pos.parse().ok().filter(|n| *n != 0).map(Some),
+        }
+    };
+    let range = LineRange {
+        start: parse(start)?.unwrap_or(1),
+        end: parse(end)?,
+    };
+    // um intervalo invertido com as duas pontas positivas nunca seleciona
+    // nada e provavelmente é um erro de digitação
+    match range.end {
+        Some(end) if end > 0 && range.start > 0 && end < range.start => None,
+        _ => Some(range),
+    }
+}
+
+impl LineRange {
+    // converte as pontas em posições absolutas depois que o total de
+    // linhas é conhecido
+    fn resolve(&self, total: u64) -> (i64, i64) {
+        let total = total as i64;
+        let absolute = |pos: i64| if pos < 0 { total + 1 + pos } else { pos };
+        (absolute(self.start), self.end.map_or(total, absolute))
+    }
+}
+
+// --------------------------------------------------
+// Decide quais linhas do fluxo são impressas. Com as duas pontas
+// absolutas nada é guardado. Quando uma delas é relativa ao fim, só se sabe
+// se a linha entra no intervalo depois de saber quantas linhas vêm depois
+// dela, então um buffer circular guarda apenas as linhas que ainda podem
+// ser impressas:
+// - com o início negativo (-K:), as últimas K linhas;
+// - com só o fim negativo (S:-K), as últimas K-1 linhas, que podem ser as
+//   que ficam de fora; a que sai do buffer já pode ser impressa
+// K vem do usuário e pode ser enorme, então o buffer não é reservado de
+// antemão: ele cresce com as linhas lidas, até no máximo K
+pub struct RangeFilter {
+    range: LineRange,
+    capacity: usize,
+    // posição e bytes já formatados de cada linha guardada
+    buffer: VecDeque<(u64, Vec<u8>)>,
+}
+
+impl RangeFilter {
+    pub fn new(range: LineRange) -> Self {
+        let lines = |pos: i64| {
+            usize::try_from(pos.unsigned_abs()).unwrap_or(usize::MAX)
+        };
+        let capacity = match (range.start, range.end) {
+            (start, _) if start < 0 => lines(start),
+            (_, Some(end)) if end < 0 => lines(end) - 1,
+            _ => 0,
+        };
+        RangeFilter {
+            range,
+            capacity,
+            buffer: VecDeque::new(),
+        }
+    }
+
+    // indica que a linha está depois do fim absoluto do intervalo e que a
+    // leitura pode parar. Com o início negativo, é preciso ler até o fim
+    // para saber onde o intervalo começa, mesmo que o fim seja absoluto
+    pub fn is_past(&self, line: u64) -> bool {
+        match self.range.end {
+            Some(end) => {
+                end > 0 && self.range.start > 0 && line as i64 > end
+            }
+            None => false,
+        }
+    }
+
+    // recebe um pedaço formatado da linha de posição line; uma mesma linha
+    // pode chegar em mais de um pedaço quando um arquivo termina sem \n
+    pub fn push(
+        &mut self,
+        line: u64,
+        bytes: &[u8],
+        out: &mut impl Write,
+    ) -> io::Result<()> {
+        if self.capacity == 0 {
+            // o início não é negativo aqui, então só falta checá-lo
+            if line as i64 >= self.range.start {
+                out.write_all(bytes)?;
+            }
+            return Ok(());
+        }
+        match self.buffer.back_mut() {
+            Some((last, pending)) if *last == line => {
+                pending.extend_from_slice(bytes);
+                return Ok(());
+            }
+            _ => self.buffer.push_back((line, bytes.to_vec())),
+        }
+        if self.buffer.len() > self.capacity {
+            if let Some((evicted, pending)) = self.buffer.pop_front() {
+                // só com o início absoluto a linha que sai pode estar
+                // no intervalo
+                if self.range.start > 0 && evicted as i64 >= self.range.start {
+                    out.write_all(&pending)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // chamado no fim da entrada, quando total é o número de linhas lidas
+    pub fn finish(self, total: u64, out: &mut impl Write) -> io::Result<()> {
+        let (start, end) = self.range.resolve(total);
+        for (line, pending) in self.buffer {
+            if (start..=end).contains(&(line as i64)) {
+                out.write_all(&pending)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_range, LineRange, RangeFilter};
+
+    #[test]
+    fn test_parse_range() {
+        let range = |start, end| Some(LineRange { start, end });
+        assert_eq!(parse_range("10:20"), range(10, Some(20)));
+        assert_eq!(parse_range("100:"), range(100, None));
+        assert_eq!(parse_range(":50"), range(1, Some(50)));
+        assert_eq!(parse_range("-30:"), range(-30, None));
+        assert_eq!(parse_range("2:-1"), range(2, Some(-1)));
+        assert_eq!(parse_range("10"), None);
+        assert_eq!(parse_range("0:5"), None);
+        assert_eq!(parse_range("5:2"), None);
+        assert_eq!(parse_range("a:b"), None);
+    }
+
+    fn select(range: &str, total: u64) -> String {
+        let mut filter = RangeFilter::new(parse_range(range).unwrap());
+        let mut out = Vec::new();
+        for line in 1..=total {
+            if filter.is_past(line) {
+                break;
+            }
+            filter
+                .push(line, format!("{}\n", line).as_bytes(), &mut out)
+                .unwrap();
+        }
+        filter.finish(total, &mut out).unwrap();
+        String::from_utf8(out).unwrap().replace('\n', " ")
+    }
+
+    #[test]
+    fn test_range_filter() {
+        assert_eq!(select("2:4", 6), "2 3 4 ");
+        assert_eq!(select("5:", 6), "5 6 ");
+        assert_eq!(select("-2:", 6), "5 6 ");
+        assert_eq!(select("2:-3", 6), "2 3 4 ");
+        assert_eq!(select("-4:-2", 6), "3 4 5 ");
+        assert_eq!(select("-10:", 3), "1 2 3 ");
+        assert_eq!(select("4:-4", 6), "");
+        assert_eq!(select("-5:17", 20), "16 17 ");
+        assert_eq!(select("-10:5", 20), "");
+    }
+}
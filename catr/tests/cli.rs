@@ -1,10 +1,16 @@
+use assert_cmd::cargo::cargo_bin;
 use assert_cmd::Command;
 use predicates::prelude::*;
 // Alphanumeric é um iterator disponibilizado para que possamos pegar
 // valores aleatórios alfanuméricos
 use rand::{distributions::Alphanumeric, Rng};
 use std::error::Error;
-use std::fs;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::process::{self, Stdio};
+use std::thread;
+use std::time::Duration;
 
 type TestResult = Result<(), Box<dyn Error>>;
 
@@ -13,6 +19,14 @@ const EMPTY: &str = "tests/inputs/empty.txt";
 const FOX: &str = "tests/inputs/fox.txt";
 const SPIDERS: &str = "tests/inputs/spiders.txt";
 const BUSTLE: &str = "tests/inputs/the-bustle.txt";
+const CONTROL: &str = "tests/inputs/control.txt";
+const BLANKS: &str = "tests/inputs/blanks.txt";
+const CRLF: &str = "tests/inputs/crlf.txt";
+const BINARY: &str = "tests/inputs/binary.bin";
+const FOX_GZ: &str = "tests/inputs/fox.txt.gz";
+const MIXED: &str = "tests/inputs/mixed.txt";
+const LATIN1: &str = "tests/inputs/latin1.txt";
+const SPIDERS_UTF16: &str = "tests/inputs/spiders.utf16.txt";
 
 // --------------------------------------------------
 #[test]
@@ -58,15 +72,61 @@ fn skips_bad_file() -> TestResult {
     Command::cargo_bin(PRG)?
         .arg(&bad)
         .assert()
-        .success() // o programa apenas pula arquivos que não existem, não mata
-        .stderr(predicate::str::is_match(expected)?); // o processo
+        .failure() // o arquivo é pulado, mas o código de saída indica o erro
+        .stderr(predicate::str::is_match(expected)?);
+    Ok(())
+}
+
+// --------------------------------------------------
+// os arquivos válidos continuam sendo impressos mesmo depois de um erro
+#[test]
+fn skips_bad_file_and_continues() -> TestResult {
+    let bad = gen_bad_file();
+    let fox = fs::read_to_string(FOX)?;
+    Command::cargo_bin(PRG)?
+        .args([FOX, &bad, FOX])
+        .assert()
+        .failure()
+        .stdout(format!("{}{}", fox, fox))
+        .stderr(predicate::str::contains("1 of 3 file(s) could not be read"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn skips_bad_file_and_continues_n() -> TestResult {
+    let bad = gen_bad_file();
+    Command::cargo_bin(PRG)?
+        .args(["-n", FOX, &bad, SPIDERS])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("     4\tcasually."))
+        .stderr(predicate::str::contains("1 of 3 file(s) could not be read"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn fails_on_directory() -> TestResult {
+    // testa tanto a cópia direta quanto a leitura por linhas
+    for args in [vec!["tests/inputs", FOX], vec!["-n", "tests/inputs", FOX]] {
+        Command::cargo_bin(PRG)?
+            .args(&args)
+            .assert()
+            .failure()
+            .stdout(predicate::str::contains("The quick brown fox"))
+            .stderr(predicate::str::is_match(
+                "tests/inputs: .* [(]os error 21[)]",
+            )?);
+    }
     Ok(())
 }
 
 // --------------------------------------------------
 fn run(args: &[&str], expected_file: &str) -> TestResult {
-    // read_to_string pois sabemos que os arquivos são pequeno o suficiente
-    let expected = fs::read_to_string(expected_file)?;
+    // fs::read em vez de read_to_string pois algumas saídas esperadas não
+    // são utf8 válido; os arquivos são pequenos o suficiente para a memória
+    let expected = fs::read(expected_file)?;
     Command::cargo_bin(PRG)?
         .args(args)
         .assert()
@@ -81,8 +141,8 @@ fn run_stdin(
     args: &[&str],
     expected_file: &str,
 ) -> TestResult {
-    let input = fs::read_to_string(input_file)?;
-    let expected = fs::read_to_string(expected_file)?;
+    let input = fs::read(input_file)?;
+    let expected = fs::read(expected_file)?;
     Command::cargo_bin(PRG)?
         .args(args)
         .write_stdin(input) // injeta no stdin da execução do comando
@@ -210,3 +270,708 @@ fn all_n() -> TestResult {
 fn all_b() -> TestResult {
     run(&[FOX, SPIDERS, BUSTLE, "-b"], "tests/expected/all.b.out")
 }
+
+// --------------------------------------------------
+#[test]
+fn control_v() -> TestResult {
+    run(&["-v", CONTROL], "tests/expected/control.txt.v.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn control_show_ends() -> TestResult {
+    run(&["--show-ends", CONTROL], "tests/expected/control.txt.E.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn control_show_tabs() -> TestResult {
+    run(&["--show-tabs", CONTROL], "tests/expected/control.txt.T.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn control_show_all() -> TestResult {
+    run(&["-A", CONTROL], "tests/expected/control.txt.A.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn control_e() -> TestResult {
+    run(&["-e", CONTROL], "tests/expected/control.txt.e.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn control_t() -> TestResult {
+    run(&["-t", CONTROL], "tests/expected/control.txt.t.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn control_n_show_all() -> TestResult {
+    run(&["-n", "-A", CONTROL], "tests/expected/control.txt.n.A.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn control_b_show_tabs() -> TestResult {
+    run(&["-b", "-T", CONTROL], "tests/expected/control.txt.b.T.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn blanks_s() -> TestResult {
+    run(&["-s", BLANKS], "tests/expected/blanks.txt.s.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn blanks_s_n() -> TestResult {
+    run(&["-s", "-n", BLANKS], "tests/expected/blanks.txt.s.n.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn blanks_s_b() -> TestResult {
+    run(
+        &["--squeeze-blank", "-b", BLANKS],
+        "tests/expected/blanks.txt.s.b.out",
+    )
+}
+
+// --------------------------------------------------
+// a sequência de linhas vazias no fim do primeiro arquivo continua no
+// início do segundo e deve ser reduzida a uma só
+#[test]
+fn blanks_s_n_across_files() -> TestResult {
+    run(
+        &["-s", "-n", BLANKS, EMPTY, BLANKS],
+        "tests/expected/blanks2.s.n.out",
+    )
+}
+
+// --------------------------------------------------
+// sem opções a saída deve ser idêntica byte a byte à entrada
+#[test]
+fn binary() -> TestResult {
+    run(&[BINARY], BINARY)
+}
+
+// --------------------------------------------------
+#[test]
+fn binary_stdin() -> TestResult {
+    run_stdin(BINARY, &["-"], BINARY)
+}
+
+// --------------------------------------------------
+#[test]
+fn binary_n() -> TestResult {
+    run(&["-n", BINARY], "tests/expected/binary.bin.n.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn binary_v() -> TestResult {
+    run(&["-v", BINARY], "tests/expected/binary.bin.v.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn crlf() -> TestResult {
+    run(&[CRLF], CRLF)
+}
+
+// --------------------------------------------------
+#[test]
+fn crlf_n() -> TestResult {
+    run(&["-n", CRLF], "tests/expected/crlf.txt.n.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn crlf_b() -> TestResult {
+    run(&["-b", CRLF], "tests/expected/crlf.txt.b.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn binary_crlf() -> TestResult {
+    run(&[BINARY, CRLF], "tests/expected/binary_crlf.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn decompress_formats() -> TestResult {
+    for ext in &["gz", "xz", "zst", "bz2"] {
+        let compressed = format!("{}.{}", BUSTLE, ext);
+        run(&["-z", &compressed], "tests/expected/the-bustle.txt.out")?;
+        run(
+            &["--decompress", "-n", &compressed],
+            "tests/expected/the-bustle.txt.n.out",
+        )?;
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn decompress_stdin() -> TestResult {
+    run_stdin(
+        &format!("{}.zst", BUSTLE),
+        &["-z", "-b", "-"],
+        "tests/expected/the-bustle.txt.b.stdin.out",
+    )
+}
+
+// --------------------------------------------------
+// arquivos que não estão compactados passam sem alteração
+#[test]
+fn decompress_passthrough() -> TestResult {
+    run(&["-z", FOX_GZ, SPIDERS, FOX], "tests/expected/decompress.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn decompress_wrong_format() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["--decompress=xz", FOX_GZ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(format!(
+            "Failed to read {}",
+            FOX_GZ
+        )));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn mixed_to_lf() -> TestResult {
+    run(&["--to-lf", MIXED], "tests/expected/mixed.txt.lf.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn mixed_to_crlf_strip_bom() -> TestResult {
+    run(
+        &["--to-crlf", "--strip-bom", MIXED],
+        "tests/expected/mixed.txt.crlf.nobom.out",
+    )
+}
+
+// --------------------------------------------------
+// a conversão acontece antes da numeração, então o CR sozinho também
+// separa linhas
+#[test]
+fn mixed_to_lf_n() -> TestResult {
+    run(
+        &["--to-lf", "--strip-bom", "-n", MIXED],
+        "tests/expected/mixed.txt.lf.nobom.n.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn crlf_to_lf_stdin() -> TestResult {
+    run_stdin(CRLF, &["--to-lf"], "tests/expected/crlf.txt.lf.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn detect() -> TestResult {
+    run(
+        &["--detect", FOX, CRLF, MIXED, EMPTY],
+        "tests/expected/detect.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_to_lf_and_to_crlf() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["--to-lf", "--to-crlf", FOX])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "The argument '--to-crlf' cannot be used with '--to-lf'",
+        ));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn binary_hexdump() -> TestResult {
+    run(&["-x", BINARY], "tests/expected/binary.bin.x.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn bustle_hexdump_cols_group() -> TestResult {
+    run(
+        &["--hexdump", "--cols", "8", "--group", "3", BUSTLE],
+        "tests/expected/the-bustle.txt.x.c8.g3.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn bustle_hexdump_offset_length() -> TestResult {
+    run(
+        &["-x", "--group", "0", "--offset", "0x10", "--length", "40", BUSTLE],
+        "tests/expected/the-bustle.txt.x.g0.s16.l40.out",
+    )
+}
+
+// --------------------------------------------------
+// os arquivos são concatenados antes do dump, como em cat a b | xxd
+#[test]
+fn hexdump_multiple_files() -> TestResult {
+    run(&["-x", FOX, SPIDERS], "tests/expected/fox_spiders.x.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn hexdump_reverse() -> TestResult {
+    run(&["-r", "tests/expected/binary.bin.x.out"], BINARY)?;
+    run_stdin(
+        "tests/expected/the-bustle.txt.x.c8.g3.out",
+        &["--reverse"],
+        BUSTLE,
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn hexdump_reverse_bad_input() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["-r", FOX])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid hex dump at line 1"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_cols() -> TestResult {
+    // como no xxd, no máximo 256 bytes por linha
+    for bad in ["0", "257", "foo"] {
+        Command::cargo_bin(PRG)?
+            .args(["-x", "--cols", bad, FOX])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(format!(
+                "illegal column count -- {}",
+                bad
+            )));
+    }
+    // em hexadecimal, o valor aparece em decimal na mensagem
+    Command::cargo_bin(PRG)?
+        .args(["-x", "--cols", "0x10000000000", FOX])
+        .assert()
+        .failure()
+        .stderr("illegal column count -- 1099511627776\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn latin1_to_utf8() -> TestResult {
+    run(
+        &["--from-encoding", "latin1", LATIN1],
+        "tests/expected/latin1.txt.utf8.out",
+    )?;
+    run(
+        &["--from-encoding", "ISO-8859-1", "-n", LATIN1],
+        "tests/expected/latin1.txt.utf8.n.out",
+    )
+}
+
+// --------------------------------------------------
+// o BOM decide a ordem dos bytes e não aparece na saída
+#[test]
+fn utf16_to_utf8() -> TestResult {
+    run(&["--from-encoding", "utf-16", SPIDERS_UTF16], SPIDERS)?;
+    run_stdin(
+        SPIDERS_UTF16,
+        &["--from-encoding", "utf-16le", "-n"],
+        "tests/expected/spiders.txt.n.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn utf8_to_utf16() -> TestResult {
+    run(
+        &["--to-encoding", "utf-16be", BUSTLE],
+        "tests/expected/the-bustle.txt.utf16be.out",
+    )?;
+    // os números de linha também são convertidos
+    run(
+        &["--to-encoding", "utf-16", "-n", BUSTLE],
+        "tests/expected/the-bustle.txt.n.utf16.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn roundtrip_latin1() -> TestResult {
+    run(
+        &["--from-encoding", "latin1", "--to-encoding", "latin1", LATIN1],
+        LATIN1,
+    )
+}
+
+// --------------------------------------------------
+// no modo normal o caractere sem representação vira "?"
+#[test]
+fn unmappable_replaced() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["--to-encoding", "latin1", BUSTLE])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Enacted upon earth,?\n"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn strict_unmappable() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["--strict", "--to-encoding", "latin1", BUSTLE])
+        .assert()
+        .failure()
+        .stdout(predicate::str::ends_with("Enacted upon earth,"))
+        .stderr(predicate::str::contains(
+            "cannot convert '—' (U+2014) to ISO-8859-1 at output offset 92",
+        ));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn strict_invalid_input() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["--strict", "--from-encoding", "utf-8", LATIN1, FOX])
+        .assert()
+        .failure()
+        .stdout(predicate::str::starts_with("Caf"))
+        .stdout(predicate::str::contains("The quick brown fox"))
+        .stderr(predicate::str::contains(format!(
+            "Failed to read {}: invalid UTF-8 sequence at byte offset 3",
+            LATIN1
+        )));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_unknown_encoding() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["--from-encoding", "klingon", FOX])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("unknown encoding -- klingon"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn spiders_number_width_separator() -> TestResult {
+    run(
+        &["-n", "--number-width", "3", "--number-separator", ": ", SPIDERS],
+        "tests/expected/spiders.txt.n.w3.s.out",
+    )
+}
+
+// --------------------------------------------------
+// as opções de formato sozinhas já ligam a numeração, como no nl
+#[test]
+fn bustle_number_zero_padded() -> TestResult {
+    run(
+        &[
+            "--number-format",
+            "rz",
+            "--number-width",
+            "4",
+            "--number-start",
+            "10",
+            "--number-increment",
+            "5",
+            BUSTLE,
+        ],
+        "tests/expected/the-bustle.txt.rz.w4.v10.i5.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn all_number_left_per_file() -> TestResult {
+    run(
+        &["-n", "--number-format", "ln", "--number-per-file", FOX, SPIDERS],
+        "tests/expected/fox_spiders.ln.per_file.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_number_width() -> TestResult {
+    // larguras acima de 1024 não são aceitas
+    for bad in ["0", "1025", "70000"] {
+        Command::cargo_bin(PRG)?
+            .args(["-n", "--number-width", bad, FOX])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(format!(
+                "illegal line number width -- {}",
+                bad
+            )));
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn bustle_range_n() -> TestResult {
+    run(
+        &["-n", "--range", "3:6", BUSTLE],
+        "tests/expected/the-bustle.txt.n.range3-6.out",
+    )
+}
+
+// --------------------------------------------------
+// o intervalo vale para os arquivos concatenados e os números mostram a
+// posição original de cada linha
+#[test]
+fn all_range_n_end_relative() -> TestResult {
+    run(
+        &["-n", "--range", "4:-3", EMPTY, FOX, SPIDERS, BUSTLE],
+        "tests/expected/all.n.range4--3.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn all_range_last_lines() -> TestResult {
+    run(
+        &["--range", "-4:", EMPTY, FOX, SPIDERS, BUSTLE],
+        "tests/expected/all.range-4.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn bustle_stdin_range() -> TestResult {
+    run_stdin(
+        BUSTLE,
+        &["--range=-3:"],
+        "tests/expected/the-bustle.txt.range-3.stdin.out",
+    )
+}
+
+// --------------------------------------------------
+// com o início negativo e o fim absoluto, o início depende do total de
+// linhas, então o arquivo é lido até o fim
+#[test]
+fn bustle_range_last_lines_to_line() -> TestResult {
+    run(
+        &["-n", "--range", "-4:8", BUSTLE],
+        "tests/expected/the-bustle.txt.n.range-4-8.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn bustle_range_last_lines_before_window() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["--range", "-4:3", BUSTLE])
+        .assert()
+        .success()
+        .stdout("");
+    Ok(())
+}
+
+// --------------------------------------------------
+// o buffer cresce com a entrada, então um início muito antes da primeira
+// linha não reserva memória para todas as linhas pedidas
+#[test]
+fn spiders_range_huge_start() -> TestResult {
+    let expected = fs::read_to_string(SPIDERS)?;
+    for range in ["-1000000000000:", "-9223372036854775807:"] {
+        Command::cargo_bin(PRG)?
+            .args(["--range", range, SPIDERS])
+            .assert()
+            .success()
+            .stdout(expected.clone());
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_range() -> TestResult {
+    for range in ["0:3", "5:2", "7"] {
+        Command::cargo_bin(PRG)?
+            .args(["--range", range, FOX])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(format!(
+                "illegal line range -- {}",
+                range
+            )));
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+// Roda o catr acompanhando um arquivo temporário enquanto change altera o
+// arquivo. Um processo sleep serve de --pid: quando ele é encerrado, o catr
+// faz uma última leitura e termina
+fn run_follow(
+    args: &[&str],
+    change: impl FnOnce(&Path) -> io::Result<()>,
+    expected: &str,
+) -> TestResult {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("log.txt");
+    fs::write(&path, "one\n")?;
+
+    let mut sleeper = process::Command::new("sleep").arg("30").spawn()?;
+    let pid = sleeper.id().to_string();
+    let catr = process::Command::new(cargo_bin(PRG))
+        .args(args)
+        .args(["--pid", &pid, "--sleep-interval", "0.1"])
+        .arg(&path)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    thread::sleep(Duration::from_millis(500));
+    change(&path)?;
+    thread::sleep(Duration::from_millis(500));
+    sleeper.kill()?;
+    // sem o wait o sleep continua existindo como zumbi e o catr não termina
+    sleeper.wait()?;
+
+    let output = catr.wait_with_output()?;
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout)?, expected);
+    Ok(())
+}
+
+// --------------------------------------------------
+fn append(path: &Path, text: &str) -> io::Result<()> {
+    OpenOptions::new().append(true).open(path)?.write_all(text.as_bytes())
+}
+
+// --------------------------------------------------
+#[test]
+fn follow_appended() -> TestResult {
+    run_follow(
+        &["-n", "-f"],
+        |path| append(path, "two\nthree\n"),
+        "     1\tone\n     2\ttwo\n     3\tthree\n",
+    )
+}
+
+// --------------------------------------------------
+// com -F, o arquivo novo criado com o mesmo nome passa a ser acompanhado
+#[test]
+fn follow_name_rotated() -> TestResult {
+    run_follow(
+        &["-n", "-F"],
+        |path| {
+            append(path, "two\n")?;
+            fs::rename(path, path.with_extension("1"))?;
+            thread::sleep(Duration::from_millis(300));
+            fs::write(path, "three\n")
+        },
+        "     1\tone\n     2\ttwo\n     3\tthree\n",
+    )
+}
+
+// --------------------------------------------------
+// com -f, o arquivo renomeado continua sendo acompanhado pelo descritor
+#[test]
+fn follow_descriptor_rotated() -> TestResult {
+    run_follow(
+        &["-f"],
+        |path| {
+            let old = path.with_extension("1");
+            fs::rename(path, &old)?;
+            fs::write(path, "new\n")?;
+            append(&old, "two\n")
+        },
+        "one\ntwo\n",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn follow_name_truncated() -> TestResult {
+    run_follow(
+        &["-F"],
+        |path| {
+            append(path, "two\n")?;
+            thread::sleep(Duration::from_millis(300));
+            fs::write(path, "new\n")
+        },
+        "one\ntwo\nnew\n",
+    )
+}
+
+// --------------------------------------------------
+// um pipe no stdin não é acompanhado; o timeout só evita que o teste fique
+// preso se o catr não terminar
+#[test]
+fn follow_stdin_pipe() -> TestResult {
+    Command::new("timeout")
+        .arg("5")
+        .arg(cargo_bin(PRG))
+        .arg("-f")
+        .write_stdin("hi\n")
+        .assert()
+        .success()
+        .stdout("hi\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_follow_no_files_remaining() -> TestResult {
+    let bad = gen_bad_file();
+    Command::new("timeout")
+        .arg("5")
+        .arg(cargo_bin(PRG))
+        .arg("-f")
+        .arg(&bad)
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("no files remaining"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_pid_without_follow() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["--pid", "1", FOX])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "--pid and --sleep-interval require --follow or -F",
+        ));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_follow_and_reverse() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["-f", "--reverse", FOX])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+    Ok(())
+}
@@ -13,6 +13,8 @@ for FILE in $INPUTS/*.txt; do
     head -c 1 $FILE > ${OUT_DIR}/${BASENAME}.c1.out
     head -c 2 $FILE > ${OUT_DIR}/${BASENAME}.c2.out
     head -c 4 $FILE > ${OUT_DIR}/${BASENAME}.c4.out
+    head -n -2 $FILE > ${OUT_DIR}/${BASENAME}.n-2.out
+    head -c -4 $FILE > ${OUT_DIR}/${BASENAME}.c-4.out
 done
 
 ALL="$INPUTS/empty.txt $INPUTS/one.txt $INPUTS/two.txt $INPUTS/three.txt \
@@ -23,3 +25,44 @@ head -n 4 $ALL > $OUT_DIR/all.n4.out
 head -c 1 $ALL > $OUT_DIR/all.c1.out
 head -c 2 $ALL > $OUT_DIR/all.c2.out
 head -c 4 $ALL > $OUT_DIR/all.c4.out
+head -n -2 $ALL > $OUT_DIR/all.n-2.out
+head -c -4 $ALL > $OUT_DIR/all.c-4.out
+
+BINARY="$INPUTS/binary.bin"
+head -c 600 $BINARY > $OUT_DIR/binary.bin.c600.out
+head -c 1M   $BINARY > $OUT_DIR/binary.bin.c1M.out
+
+head -q -n 2 $ALL > $OUT_DIR/all.n2.q.out
+head -v $INPUTS/one.txt > $OUT_DIR/one.txt.v.out
+# --header-format não existe no head do GNU; o cabeçalho é montado com o
+# número de linhas que o head mostra de cada arquivo
+for FILE in $INPUTS/one.txt $INPUTS/ten.txt; do
+    [[ $FILE != $INPUTS/one.txt ]] && echo
+    echo "# $FILE ($(head -n 2 $FILE | wc -l) lines)"
+    head -n 2 $FILE
+done > $OUT_DIR/one_ten.n2.header.out
+# com um único arquivo, o modelo também mostra o cabeçalho
+FILE=$INPUTS/one.txt
+{
+    echo "# $FILE ($(head -n 2 $FILE | wc -c) bytes)"
+    head -n 2 $FILE
+} > $OUT_DIR/one.txt.n2.header.out
+
+# o head do GNU não tem -m; como os trechos terminam em caracteres
+# completos, a saída equivale a uma quantidade de bytes:
+# "Öne l" são 5 caracteres em 6 bytes, "cafe" seguido do acento combinante e
+# do espaço são 6 caracteres em 7 bytes e "café n" são 6 grapheme clusters
+# em 8 bytes
+head -c 6 $INPUTS/one.txt       > $OUT_DIR/one.txt.m5.out
+head -c 7 $INPUTS/combining.txt > $OUT_DIR/combining.txt.m6.out
+head -c 8 $INPUTS/combining.txt > $OUT_DIR/combining.txt.m6.graphemes.out
+
+# --until e --through equivalem a apagar ou encerrar no primeiro casamento
+sed '/^f/,$d' $INPUTS/ten.txt > $OUT_DIR/ten.txt.until.out
+sed '/^f/q' $INPUTS/ten.txt > $OUT_DIR/ten.txt.through.out
+head -n 2 $INPUTS/ten.txt | sed '/^f/q' > $OUT_DIR/ten.txt.through.n2.out
+for FILE in $INPUTS/one.txt $INPUTS/two.txt; do
+    [[ $FILE != $INPUTS/one.txt ]] && echo
+    echo "==> $FILE <=="
+    sed '/lines/q' $FILE
+done > $OUT_DIR/one_two.through.out
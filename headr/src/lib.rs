@@ -1,15 +1,43 @@
 use clap::{App, Arg};
+use regex::bytes::Regex;
+use std::collections::VecDeque;
 use std::error::Error;
 use std::fs::File;
-use std::io::{self, BufRead, BufReader, Read};
+use std::io::{self, BufRead, BufReader, Write};
+use unicode_segmentation::UnicodeSegmentation;
 
 type MyResult<T> = Result<T, Box<dyn Error>>;
 
 #[derive(Debug)]
 pub struct Config {
     files: Vec<String>,
-    lines: usize,
-    bytes: Option<usize>,
+    lines: Count,
+    bytes: Option<Count>,
+    chars: Option<usize>,
+    stop: Option<Stop>,
+    // -m conta grapheme clusters em vez de caracteres
+    graphemes: bool,
+    quiet: bool,
+    verbose: bool,
+    // modelo do cabeçalho, com {name}, {lines} e {bytes}
+    header_format: Option<String>,
+}
+
+// Quantidade de linhas ou bytes que serão mostrados. Um valor negativo, como
+// no head do GNU (-n -5), mostra tudo exceto as últimas K linhas ou bytes
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Count {
+    First(usize),
+    AllButLast(usize),
+}
+
+// Padrão de --until e --through que encerra a leitura de cada arquivo
+#[derive(Debug)]
+enum Stop {
+    // a linha que casa com o padrão não é mostrada
+    Until(Regex),
+    // a linha que casa com o padrão é a última mostrada
+    Through(Regex),
 }
 
 // --------------------------------------------------
@@ -23,9 +51,11 @@ pub fn get_args() -> MyResult<Config> {
                 .short("n")
                 .long("lines")
                 .value_name("LINES")
-                .help("Number of lines")
-                .default_value("10"), // por padrão mostra as 10 primeiras linhas
+                .help("Number of lines (-K: all but the last K)")
+                .default_value("10") // por padrão mostra as 10 primeiras linhas
                 // não precisa usar takes_value, pois já define um valor padrão
+                // aceita valores que começam com "-", como -n -5
+                .allow_hyphen_values(true),
         )
         .arg(
             Arg::with_name("bytes")
@@ -34,7 +64,64 @@ pub fn get_args() -> MyResult<Config> {
                 .value_name("BYTES")
                 .takes_value(true)       // flag que aceita valores
                 .conflicts_with("lines") // não aceita mostrar linhas e bytes
-                .help("Number of bytes"), // ao mesmo tempo
+                .allow_hyphen_values(true) // ao mesmo tempo
+                .help("Number of bytes (-K: all but the last K)"),
+        )
+        .arg(
+            Arg::with_name("chars")
+                .short("m")
+                .long("chars")
+                .value_name("CHARS")
+                .help("Number of characters")
+                // assim como bytes, não pode ser usado com linhas ou bytes
+                .conflicts_with_all(&["lines", "bytes"]),
+        )
+        .arg(
+            Arg::with_name("graphemes")
+                .long("graphemes")
+                .help("Count grapheme clusters instead of characters with -m")
+                .takes_value(false)
+                .requires("chars"),
+        )
+        .arg(
+            Arg::with_name("until")
+                .long("until")
+                .value_name("REGEX")
+                .help("Stop before the first line matching REGEX")
+                .conflicts_with_all(&["bytes", "chars", "through"]),
+        )
+        .arg(
+            Arg::with_name("through")
+                .long("through")
+                .value_name("REGEX")
+                .help("Stop after the first line matching REGEX")
+                .conflicts_with_all(&["bytes", "chars"]),
+        )
+        .arg(
+            Arg::with_name("quiet")
+                .short("q")
+                .long("quiet")
+                .help("Never print headers giving file names")
+                .takes_value(false)
+                .conflicts_with("verbose"),
+        )
+        .arg(
+            Arg::with_name("verbose")
+                .short("v")
+                .long("verbose")
+                .help("Always print headers giving file names")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("header_format")
+                .long("header-format")
+                .value_name("FORMAT")
+                .help(
+                    "Header template with {name}, {lines} and {bytes}; \
+                     implies -v. {lines} and {bytes} keep each file's \
+                     output in memory until it is counted",
+                )
+                .conflicts_with("quiet"),
         )
         .arg(
             Arg::with_name("files")
@@ -48,7 +135,7 @@ pub fn get_args() -> MyResult<Config> {
     let lines = matches
         .value_of("lines")
         // passa o valor que vem de value_of para a função de parsing
-        .map(parse_positive_int) 
+        .map(parse_count)
         // o map retorna Option<Result>, transpose transforma em Result<Option>
         .transpose()             
         // passa o retorno para um map que captura erros
@@ -56,10 +143,38 @@ pub fn get_args() -> MyResult<Config> {
 
     let bytes = matches
         .value_of("bytes")
-        .map(parse_positive_int)
+        .map(parse_count)
         .transpose()
         .map_err(|e| format!("illegal byte count -- {}", e))?;
 
+    // compila o padrão uma única vez, antes de abrir os arquivos
+    let stop = if let Some(pattern) = matches.value_of("until") {
+        Some(Stop::Until(parse_regex(pattern)?))
+    } else if let Some(pattern) = matches.value_of("through") {
+        Some(Stop::Through(parse_regex(pattern)?))
+    } else {
+        None
+    };
+    // com um padrão, -n é apenas um limite e só vale se for informado; sem
+    // ele a leitura vai até o padrão ou o fim do arquivo
+    let lines = match lines {
+        Some(Count::AllButLast(_)) if stop.is_some() => {
+            return Err(From::from(
+                "--until and --through need a positive line count",
+            ))
+        }
+        _ if stop.is_some() && matches.occurrences_of("lines") == 0 => {
+            Some(Count::First(usize::MAX))
+        }
+        lines => lines,
+    };
+
+    let chars = matches
+        .value_of("chars")
+        .map(parse_positive_int)
+        .transpose()
+        .map_err(|e| format!("illegal character count -- {}", e))?;
+
     Ok(Config {
         // busca o valor passado substituindo code points utf8 invalidos por um
         // code point valido que não tem significado especial, um caractere
@@ -67,72 +182,71 @@ pub fn get_args() -> MyResult<Config> {
         files: matches.values_of_lossy("files").unwrap(),
         lines: lines.unwrap(),
         bytes, // o valor de bytes é mantido como Option
+        chars,
+        stop,
+        graphemes: matches.is_present("graphemes"),
+        quiet: matches.is_present("quiet"),
+        verbose: matches.is_present("verbose"),
+        header_format: matches.value_of("header_format").map(String::from),
     })
 }
 
 // --------------------------------------------------
 pub fn run(config: Config) -> MyResult<()> {
     let num_files = config.files.len();
+    // por padrão o cabeçalho só aparece com mais de um arquivo; -q e -v
+    // escolhem explicitamente, e um modelo em --header-format vale como -v
+    let show_headers = !config.quiet
+        && (config.verbose
+            || config.header_format.is_some()
+            || num_files > 1);
+    // {lines} e {bytes} se referem ao que é mostrado do arquivo, então essa
+    // saída precisa ser gerada antes do cabeçalho
+    let needs_counts = config.header_format.as_ref().is_some_and(|format| {
+        format.contains("{lines}") || format.contains("{bytes}")
+    });
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
 
     for (file_num, filename) in config.files.iter().enumerate() {
         match open(filename) {
             Err(err) => eprintln!("{}: {}", filename, err),
-            Ok(mut file) => {
-                // se existir mais de um arquivo, adiciona um cabeçalho
-                // indicando onde começa a saída do comando para aquele
-                // arquivo
-                if num_files > 1 {
-                    println!(
-                        "{}==> {} <==",
-                        // se for o primeiro arquivo, não pula linha
-                        if file_num > 0 { "\n" } else { "" },
-                        filename
-                    );
+            Ok(file) => {
+                if !show_headers {
+                    head(file, &config, &mut out)?;
+                    continue;
                 }
-
-                if let Some(num_bytes) = config.bytes {
-                    /*
-                    // le o arquivo numa string, converte em bytes e converte
-                    // os bytes desejados em string de volta
-                    // não é indicado pois se o arquivo for muito grande será
-                    // lido por inteiro e pode faltar memória, e se estiver 
-                    // vazio a leitura do buffer incorre em erro, pois não há
-                    // elementos
-                    let mut contents = String::new();
-                    file.read_to_string(&mut contents)?; 
-                    let bytes = contents.as_bytes();
-                    print!("{}", String::from_utf8_lossy(&bytes[..num_bytes]));
-                    */
-                    // BufRead::take() retorna um handle que lerá no máximo
-                    // o número de bytes informado
-                    let mut handle = file.take(num_bytes as u64);
-                    let mut buffer = vec![0; num_bytes];
-                    // a quantidade que ele vai tentar ler é o tamanho
-                    // do buffer, ainda que o limite seja maior (passado no take)
-                    handle.read(&mut buffer)?;
-                    //alternativa usando turbofish
-                    //let bytes = file.bytes().take(num_bytes).collect::<Result<Vec<_>, _>>();
-                    print!(
-                        "{}",
-                        // converte os bytes em string de forma que se não for
-                        // utf8 valido substitui por um símbolo que denota
-                        // um caractere desconhecido
-                        String::from_utf8_lossy(&buffer)
-                    );
-                } else {
-                    // line funciona como um buffer que será preenchido
-                    // a cada iteração
-                    let mut line = String::new();
-                    for _ in 0..config.lines {
-                        // BufRead::read_line lê os bytes até encontrar um 
-                        // delimitador de linha ou EOF, mantendo o delimitador
-                        // retornando o número de bytes que fora lidos no buffer
-                        let bytes = file.read_line(&mut line)?;
-                        if bytes == 0 {
-                            break;
-                        }
-                        print!("{}", line);
-                        line.clear();
+                // se for o primeiro arquivo, não pula linha
+                if file_num > 0 {
+                    writeln!(out)?;
+                }
+                // adiciona um cabeçalho indicando onde começa a saída do
+                // comando para aquele arquivo
+                match &config.header_format {
+                    None => {
+                        writeln!(out, "==> {} <==", filename)?;
+                        head(file, &config, &mut out)?;
+                    }
+                    // a saída fica em memória apenas quando o modelo usa as
+                    // contagens, e no máximo o que seria impresso de
+                    // qualquer jeito
+                    Some(format) if needs_counts => {
+                        let mut buffer = Vec::new();
+                        head(file, &config, &mut buffer)?;
+                        let lines = buffer.iter().filter(|&&b| b == b'\n');
+                        let header = format_header(
+                            format,
+                            filename,
+                            lines.count(),
+                            buffer.len(),
+                        );
+                        writeln!(out, "{}", header)?;
+                        out.write_all(&buffer)?;
+                    }
+                    Some(format) => {
+                        let header = format_header(format, filename, 0, 0);
+                        writeln!(out, "{}", header)?;
+                        head(file, &config, &mut out)?;
                     }
                 }
             }
@@ -159,6 +273,224 @@ pub fn run(config: Config) -> MyResult<()> {
     Ok(())
 }
 
+// --------------------------------------------------
+// Escreve em out o trecho do arquivo escolhido por -n ou -c
+fn head(
+    mut file: impl BufRead,
+    config: &Config,
+    out: &mut impl Write,
+) -> MyResult<()> {
+    if let Some(num_chars) = config.chars {
+        print_chars(file, num_chars, config.graphemes, out)?;
+    } else if let Some(Count::AllButLast(num_bytes)) = config.bytes {
+        print_all_but_last_bytes(file, num_bytes, out)?;
+    } else if let Some(Count::First(num_bytes)) = config.bytes {
+        /*
+        // le o arquivo numa string, converte em bytes e converte
+        // os bytes desejados em string de volta
+        // não é indicado pois se o arquivo for muito grande será
+        // lido por inteiro e pode faltar memória, e se estiver 
+        // vazio a leitura do buffer incorre em erro, pois não há
+        // elementos
+        let mut contents = String::new();
+        file.read_to_string(&mut contents)?; 
+        let bytes = contents.as_bytes();
+        print!("{}", String::from_utf8_lossy(&bytes[..num_bytes]));
+        */
+        // BufRead::take() retorna um handle que lerá no máximo
+        // o número de bytes informado. Uma única chamada a read
+        // pode devolver menos bytes que o pedido (em um pipe,
+        // por exemplo), então io::copy repete a leitura até
+        // atingir o limite ou o fim do arquivo. Os bytes são
+        // escritos como foram lidos, sem conversão para utf8,
+        // para que arquivos binários saiam intactos
+        let mut handle = file.take(num_bytes as u64);
+        io::copy(&mut handle, out)?;
+    } else if let Count::AllButLast(num_lines) = config.lines {
+        print_all_but_last_lines(file, num_lines, out)?;
+    } else if let Count::First(num_lines) = config.lines {
+        // line funciona como um buffer que será preenchido
+        // a cada iteração. Como os padrões são aplicados sobre bytes, a
+        // linha não precisa ser utf8 válido
+        let mut line = Vec::new();
+        for _ in 0..num_lines {
+            // BufRead::read_until lê os bytes até encontrar o
+            // delimitador de linha ou EOF, mantendo o delimitador
+            // retornando o número de bytes que fora lidos no buffer
+            let bytes = file.read_until(b'\n', &mut line)?;
+            if bytes == 0 {
+                break;
+            }
+            // o padrão é comparado sem o \n, assim "^$" casa com uma
+            // linha vazia
+            let content = line.strip_suffix(b"\n").unwrap_or(&line);
+            match &config.stop {
+                Some(Stop::Until(re)) if re.is_match(content) => break,
+                Some(Stop::Through(re)) if re.is_match(content) => {
+                    out.write_all(&line)?;
+                    break;
+                }
+                _ => out.write_all(&line)?,
+            }
+            line.clear();
+        }
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+// Escreve os primeiros num_chars caracteres (ou grapheme clusters) do
+// arquivo, sem nunca cortar uma sequência utf8 no meio. A entrada é lida
+// por linhas: nem um caractere nem um grapheme cluster atravessam um \n
+fn print_chars(
+    mut file: impl BufRead,
+    num_chars: usize,
+    graphemes: bool,
+    out: &mut impl Write,
+) -> MyResult<()> {
+    let mut remaining = num_chars;
+    let mut line = Vec::new();
+    while remaining > 0 {
+        line.clear();
+        if file.read_until(b'\n', &mut line)? == 0 {
+            break;
+        }
+        let (len, taken) = take_chars(&line, remaining, graphemes);
+        out.write_all(&line[..len])?;
+        remaining -= taken;
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+// Retorna quantos bytes do início de line formam até max caracteres (ou
+// grapheme clusters) e quantos foram contados. Bytes que não são utf8
+// válido contam como um caractere cada e são mantidos como estão
+fn take_chars(line: &[u8], max: usize, graphemes: bool) -> (usize, usize) {
+    let mut len = 0;
+    let mut taken = 0;
+    for chunk in line.utf8_chunks() {
+        let valid = chunk.valid();
+        // posição, dentro do trecho válido, do fim de cada unidade
+        let ends: Box<dyn Iterator<Item = usize>> = if graphemes {
+            Box::new(valid.grapheme_indices(true).map(|(i, g)| i + g.len()))
+        } else {
+            Box::new(valid.char_indices().map(|(i, c)| i + c.len_utf8()))
+        };
+        let start = len;
+        for end in ends {
+            if taken == max {
+                return (len, taken);
+            }
+            taken += 1;
+            len = start + end;
+        }
+        for _ in chunk.invalid() {
+            if taken == max {
+                return (len, taken);
+            }
+            taken += 1;
+            len += 1;
+        }
+    }
+    (len, taken)
+}
+
+// --------------------------------------------------
+// Substitui {name}, {lines} e {bytes} no modelo de --header-format. Chaves
+// que não formam um desses campos são mantidas como estão
+fn format_header(
+    format: &str,
+    name: &str,
+    lines: usize,
+    bytes: usize,
+) -> String {
+    let fields = [
+        ("{name}", name.to_string()),
+        ("{lines}", lines.to_string()),
+        ("{bytes}", bytes.to_string()),
+    ];
+    let mut header = String::new();
+    let mut rest = format;
+    // percorre o modelo uma única vez, assim um nome de arquivo que contém
+    // "{lines}" não é substituído de novo
+    while let Some(start) = rest.find('{') {
+        header.push_str(&rest[..start]);
+        rest = &rest[start..];
+        match fields.iter().find(|(key, _)| rest.starts_with(key)) {
+            Some((key, value)) => {
+                header.push_str(value);
+                rest = &rest[key.len()..];
+            }
+            None => {
+                header.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    header.push_str(rest);
+    header
+}
+
+// --------------------------------------------------
+// Só se sabe que uma linha não está entre as últimas K quando outras K linhas
+// chegam depois dela, então as K linhas mais recentes ficam guardadas em um
+// buffer circular e cada linha é impressa quando sai dele. A memória usada
+// depende só de K, e não do tamanho da entrada, o que permite ler do stdin.
+// Como K pode ser bem maior que a entrada, o buffer não é reservado de
+// antemão e cresce com as linhas lidas
+fn print_all_but_last_lines(
+    mut file: impl BufRead,
+    num_lines: usize,
+    out: &mut impl Write,
+) -> MyResult<()> {
+    let mut pending: VecDeque<Vec<u8>> = VecDeque::new();
+    loop {
+        // as linhas são lidas como bytes, já que vão apenas ser repassadas
+        let mut line = Vec::new();
+        if file.read_until(b'\n', &mut line)? == 0 {
+            break;
+        }
+        pending.push_back(line);
+        if pending.len() > num_lines {
+            if let Some(line) = pending.pop_front() {
+                out.write_all(&line)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+// Mesma ideia de print_all_but_last_lines, guardando os últimos K bytes. A
+// entrada é lida em blocos e o excesso no começo do buffer é escrito de uma
+// vez, em vez de byte a byte
+fn print_all_but_last_bytes(
+    mut file: impl BufRead,
+    num_bytes: usize,
+    out: &mut impl Write,
+) -> MyResult<()> {
+    let mut pending: VecDeque<u8> = VecDeque::new();
+    loop {
+        let chunk = file.fill_buf()?;
+        if chunk.is_empty() {
+            break;
+        }
+        pending.extend(chunk);
+        let len = chunk.len();
+        file.consume(len);
+        if pending.len() > num_bytes {
+            let excess = pending.len() - num_bytes;
+            // o VecDeque pode estar dividido em duas fatias na memória
+            let (front, back) = pending.as_slices();
+            let from_front = excess.min(front.len());
+            out.write_all(&front[..from_front])?;
+            out.write_all(&back[..excess - from_front])?;
+            pending.drain(..excess);
+        }
+    }
+    Ok(())
+}
 
 // --------------------------------------------------
 fn open(filename: &str) -> MyResult<Box<dyn BufRead>> {
@@ -169,25 +501,117 @@ fn open(filename: &str) -> MyResult<Box<dyn BufRead>> {
 }
 
 fn parse_positive_int(val: &str) -> MyResult<usize> {
-    // faz o parsing do &str em um usize (inferido pelo retorno da função)
-    match val.parse() {
+    // separa o número do sufixo multiplicador (1K, 10MiB...)
+    let num_len = val
+        .trim_end_matches(|c: char| c.is_ascii_alphabetic())
+        .len();
+    let (num, suffix) = val.split_at(num_len);
+    // faz o parsing do &str em um usize. checked_mul retorna None se a
+    // multiplicação não couber em um usize
+    let parsed = num
+        .parse::<usize>()
+        .ok()
+        .zip(parse_multiplier(suffix))
+        .and_then(|(n, multiplier)| n.checked_mul(multiplier));
+    match parsed {
         // if n > 0 é um guard, uma checagem adicional ao pattern matching
-        Ok(n) if n > 0 => Ok(n),
-        /*
-        Ok(n) => {
-            if n > 0 {
-                Ok(n)
-            } else {
-                Err(From::from(val))
-            }
-        }
-        */
+        Some(n) if n > 0 => Ok(n),
         // std::convert::From é uma trait que define conversão entre tipos
         // aqui converte val (&str) em um Erro
         _ => Err(From::from(val)),
     }
 }
 
+// --------------------------------------------------
+// Sufixos aceitos pelo head do GNU: b são blocos de 512 bytes, uma letra
+// sozinha ou seguida de iB é uma potência de 1024 e a letra seguida de B é
+// uma potência de 1000 (K e KiB = 1024, kB = 1000). Retorna None para um
+// sufixo desconhecido ou que não cabe em um usize
+fn parse_multiplier(suffix: &str) -> Option<usize> {
+    if suffix.is_empty() {
+        return Some(1);
+    }
+    if suffix == "b" {
+        return Some(512);
+    }
+    let mut chars = suffix.chars();
+    let exponent = match chars.next()? {
+        // k e m minúsculos também são aceitos pelo GNU
+        'K' | 'k' => 1,
+        'M' | 'm' => 2,
+        'G' => 3,
+        'T' => 4,
+        'P' => 5,
+        'E' => 6,
+        'Z' => 7,
+        'Y' => 8,
+        _ => return None,
+    };
+    let base: usize = match chars.as_str() {
+        "" | "iB" => 1024,
+        "B" => 1000,
+        _ => return None,
+    };
+    base.checked_pow(exponent)
+}
+
+// --------------------------------------------------
+fn parse_regex(pattern: &str) -> MyResult<Regex> {
+    Regex::new(pattern)
+        .map_err(|_| From::from(format!("illegal pattern -- {}", pattern)))
+}
+
+// --------------------------------------------------
+// Um "-" na frente do número indica que as últimas K linhas ou bytes serão
+// omitidos. O erro continua sendo o valor original, para que a mensagem
+// mostre exatamente o que foi informado
+fn parse_count(val: &str) -> MyResult<Count> {
+    let count = match val.strip_prefix('-') {
+        Some(num) => parse_positive_int(num).map(Count::AllButLast),
+        None => parse_positive_int(val).map(Count::First),
+    };
+    count.map_err(|_| From::from(val))
+}
+
+#[test]
+fn test_take_chars() {
+    // "Ö" ocupa dois bytes e não pode ser cortado
+    assert_eq!(take_chars("Öne".as_bytes(), 1, false), (2, 1));
+    assert_eq!(take_chars("Öne".as_bytes(), 5, false), (4, 3));
+    // "e" seguido do acento agudo combinante é um único grapheme cluster
+    let line = "e\u{301}x\n".as_bytes();
+    assert_eq!(take_chars(line, 1, false), (1, 1));
+    assert_eq!(take_chars(line, 1, true), (3, 1));
+    assert_eq!(take_chars(line, 3, true), (5, 3));
+    // bytes inválidos contam como um caractere cada
+    assert_eq!(take_chars(b"a\xffb", 2, false), (2, 2));
+    assert_eq!(take_chars(b"a\xffb", 3, true), (3, 3));
+}
+
+#[test]
+fn test_format_header() {
+    assert_eq!(
+        format_header("# {name} ({lines} lines)", "a.txt", 3, 10),
+        "# a.txt (3 lines)"
+    );
+    assert_eq!(format_header("{bytes}{", "a", 0, 7), "7{");
+    assert_eq!(
+        format_header("{nome} {name}", "{lines}", 1, 1),
+        "{nome} {lines}"
+    );
+}
+
+#[test]
+fn test_parse_count() {
+    assert_eq!(parse_count("3").unwrap(), Count::First(3));
+    assert_eq!(parse_count("-3").unwrap(), Count::AllButLast(3));
+    let res = parse_count("-foo");
+    assert!(res.is_err());
+    assert_eq!(res.unwrap_err().to_string(), "-foo".to_string());
+    assert!(parse_count("--3").is_err());
+    assert!(parse_count("-0").is_err());
+}
+
 #[test]
 fn test_parse_positive_int() {
     // 3 é um inteiro válido
@@ -202,4 +626,20 @@ fn test_parse_positive_int() {
     let res = parse_positive_int("0");
     assert!(res.is_err());
     assert_eq!(res.unwrap_err().to_string(), "0".to_string());
+    // sufixos binários e decimais
+    assert_eq!(parse_positive_int("2b").unwrap(), 1024);
+    assert_eq!(parse_positive_int("1K").unwrap(), 1024);
+    assert_eq!(parse_positive_int("1KiB").unwrap(), 1024);
+    assert_eq!(parse_positive_int("3kB").unwrap(), 3000);
+    assert_eq!(parse_positive_int("2M").unwrap(), 2 * 1024 * 1024);
+    assert_eq!(parse_positive_int("1MB").unwrap(), 1_000_000);
+    assert_eq!(parse_positive_int("1GiB").unwrap(), 1 << 30);
+    // sufixos desconhecidos, sem número ou grandes demais
+    assert!(parse_positive_int("1X").is_err());
+    assert!(parse_positive_int("1KB2").is_err());
+    assert!(parse_positive_int("K").is_err());
+    assert!(parse_positive_int("0K").is_err());
+    let res = parse_positive_int("99999E");
+    assert!(res.is_err());
+    assert_eq!(res.unwrap_err().to_string(), "99999E".to_string());
 }
@@ -1,3 +1,6 @@
+// os testes passam os argumentos como .args(&[...]), como nos outros crates
+#![allow(clippy::needless_borrows_for_generic_args)]
+
 use assert_cmd::Command;
 use predicates::prelude::*;
 use rand::{distributions::Alphanumeric, Rng};
@@ -15,6 +18,8 @@ const ONE: &str = "./tests/inputs/one.txt";
 const TWO: &str = "./tests/inputs/two.txt";
 const THREE: &str = "./tests/inputs/three.txt";
 const TEN: &str = "./tests/inputs/ten.txt";
+const COMBINING: &str = "./tests/inputs/combining.txt";
+const BINARY: &str = "./tests/inputs/binary.bin";
 
 // --------------------------------------------------
 fn random_string() -> String {
@@ -63,6 +68,22 @@ fn dies_bad_lines() -> TestResult {
     Ok(())
 }
 
+// --------------------------------------------------
+#[test]
+fn dies_bad_suffix() -> TestResult {
+    // sufixo desconhecido e valor que não cabe em um usize
+    for bad in ["10X", "1KB2", "99999E"] {
+        let expected = format!("illegal byte count -- {}", bad);
+        Command::cargo_bin(PRG)?
+            .args(["-c", bad, EMPTY])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(expected));
+    }
+
+    Ok(())
+}
+
 // --------------------------------------------------
 #[test]
 fn dies_bytes_and_lines() -> TestResult {
@@ -93,17 +114,16 @@ fn skips_bad_file() -> TestResult {
 
 // --------------------------------------------------
 fn run(args: &[&str], expected_file: &str) -> TestResult {
-    // lida utf8 inválido
+    // compara os bytes, já que -c pode cortar um caractere utf8 no meio
     let mut file = File::open(expected_file)?;
-    let mut buffer = Vec::new();
-    file.read_to_end(&mut buffer)?;
-    let expected = String::from_utf8_lossy(&buffer);
+    let mut expected = Vec::new();
+    file.read_to_end(&mut expected)?;
 
     Command::cargo_bin(PRG)?
         .args(args)
         .assert()
         .success()
-        .stdout(predicate::eq(&expected.as_bytes() as &[u8]));
+        .stdout(predicate::eq(expected.as_slice()));
 
     Ok(())
 }
@@ -114,18 +134,17 @@ fn run_stdin(
     input_file: &str,
     expected_file: &str,
 ) -> TestResult {
-    // lida utf8 inválido
+    // compara os bytes, já que -c pode cortar um caractere utf8 no meio
     let mut file = File::open(expected_file)?;
-    let mut buffer = Vec::new();
-    file.read_to_end(&mut buffer)?;
-    let expected = String::from_utf8_lossy(&buffer);
-    let input = fs::read_to_string(input_file)?;
+    let mut expected = Vec::new();
+    file.read_to_end(&mut expected)?;
+    let input = fs::read(input_file)?;
 
     Command::cargo_bin(PRG)?
         .write_stdin(input)
         .args(args)
         .assert()
-        .stdout(predicate::eq(&expected.as_bytes() as &[u8]));
+        .stdout(predicate::eq(expected.as_slice()));
 
     Ok(())
 }
@@ -374,6 +393,48 @@ fn ten_c4_stdin() -> TestResult {
     run_stdin(&["-c", "4"], TEN, "tests/expected/ten.txt.c4.out")
 }
 
+#[test]
+fn ten_n_minus2() -> TestResult {
+    run(&[TEN, "-n", "-2"], "tests/expected/ten.txt.n-2.out")
+}
+
+#[test]
+fn ten_c_minus4() -> TestResult {
+    run(&[TEN, "-c", "-4"], "tests/expected/ten.txt.c-4.out")
+}
+
+#[test]
+fn ten_n_minus2_stdin() -> TestResult {
+    run_stdin(&["-n", "-2"], TEN, "tests/expected/ten.txt.n-2.out")
+}
+
+#[test]
+fn ten_c_minus4_stdin() -> TestResult {
+    run_stdin(&["-c", "-4"], TEN, "tests/expected/ten.txt.c-4.out")
+}
+
+// ten.txt tem menos de 1024 linhas, então é mostrado inteiro
+#[test]
+fn ten_n1k() -> TestResult {
+    run(&[TEN, "-n", "1K"], "tests/expected/ten.txt.out")
+}
+
+#[test]
+fn one_n_minus2() -> TestResult {
+    run(&[ONE, "-n", "-2"], "tests/expected/one.txt.n-2.out")
+}
+
+// K muito maior que a entrada não reserva memória para K linhas ou bytes
+#[test]
+fn ten_n_minus_huge_stdin() -> TestResult {
+    run_stdin(&["-n", "-100000000000"], TEN, "tests/expected/empty.txt.out")
+}
+
+#[test]
+fn ten_c_minus_huge_stdin() -> TestResult {
+    run_stdin(&["-c", "-1T"], TEN, "tests/expected/empty.txt.out")
+}
+
 // --------------------------------------------------
 #[test]
 fn multiple_files() -> TestResult {
@@ -419,3 +480,175 @@ fn multiple_files_c4() -> TestResult {
         "tests/expected/all.c4.out",
     )
 }
+
+#[test]
+fn multiple_files_n_minus2() -> TestResult {
+    run(
+        &["-n", "-2", EMPTY, ONE, TWO, THREE, TEN],
+        "tests/expected/all.n-2.out",
+    )
+}
+
+#[test]
+fn multiple_files_c_minus4() -> TestResult {
+    run(
+        &["-c", "-4", EMPTY, ONE, TWO, THREE, TEN],
+        "tests/expected/all.c-4.out",
+    )
+}
+
+// --------------------------------------------------
+// os bytes saem exatamente como estão no arquivo, incluindo zeros e bytes
+// que não formam utf8 válido
+#[test]
+fn binary_c600() -> TestResult {
+    run(&["-c", "600", BINARY], "tests/expected/binary.bin.c600.out")
+}
+
+#[test]
+fn binary_c600_stdin() -> TestResult {
+    run_stdin(&["-c", "600"], BINARY, "tests/expected/binary.bin.c600.out")
+}
+
+// pedir mais bytes do que o arquivo tem não completa a saída com zeros
+#[test]
+fn binary_c1m() -> TestResult {
+    run(&["-c", "1M", BINARY], "tests/expected/binary.bin.c1M.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn multiple_files_quiet() -> TestResult {
+    run(
+        &["-q", "-n", "2", EMPTY, ONE, TWO, THREE, TEN],
+        "tests/expected/all.n2.q.out",
+    )
+}
+
+#[test]
+fn one_verbose() -> TestResult {
+    run(&["-v", ONE], "tests/expected/one.txt.v.out")
+}
+
+#[test]
+fn header_format() -> TestResult {
+    run(
+        &["--header-format", "# {name} ({lines} lines)", "-n", "2", ONE, TEN],
+        "tests/expected/one_ten.n2.header.out",
+    )
+}
+
+#[test]
+fn header_format_one_file() -> TestResult {
+    run(
+        &["--header-format", "# {name} ({bytes} bytes)", "-n", "2", ONE],
+        "tests/expected/one.txt.n2.header.out",
+    )
+}
+
+#[test]
+fn dies_quiet_and_verbose() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["-q", "-v", ONE])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn one_m5() -> TestResult {
+    run(&["-m", "5", ONE], "tests/expected/one.txt.m5.out")
+}
+
+#[test]
+fn one_m5_stdin() -> TestResult {
+    run_stdin(&["-m", "5"], ONE, "tests/expected/one.txt.m5.out")
+}
+
+#[test]
+fn combining_m6() -> TestResult {
+    run(&["-m", "6", COMBINING], "tests/expected/combining.txt.m6.out")
+}
+
+#[test]
+fn combining_m6_graphemes() -> TestResult {
+    run(
+        &["-m", "6", "--graphemes", COMBINING],
+        "tests/expected/combining.txt.m6.graphemes.out",
+    )
+}
+
+#[test]
+fn dies_bad_chars() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["-m", "0", ONE])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("illegal character count -- 0"));
+
+    Ok(())
+}
+
+#[test]
+fn dies_chars_and_bytes() -> TestResult {
+    let msg = "The argument '--bytes <BYTES>' cannot be \
+               used with '--chars <CHARS>'";
+    Command::cargo_bin(PRG)?
+        .args(["-m", "1", "-c", "2", ONE])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(msg));
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn ten_until() -> TestResult {
+    run(&["--until", "^f", TEN], "tests/expected/ten.txt.until.out")
+}
+
+#[test]
+fn ten_through() -> TestResult {
+    run(&["--through", "^f", TEN], "tests/expected/ten.txt.through.out")
+}
+
+#[test]
+fn ten_through_stdin() -> TestResult {
+    run_stdin(
+        &["--through", "^f"],
+        TEN,
+        "tests/expected/ten.txt.through.out",
+    )
+}
+
+// -n continua valendo como limite
+#[test]
+fn ten_through_n2() -> TestResult {
+    run(
+        &["--through", "^f", "-n", "2", TEN],
+        "tests/expected/ten.txt.through.n2.out",
+    )
+}
+
+#[test]
+fn multiple_files_through() -> TestResult {
+    run(
+        &["--through", "lines", ONE, TWO],
+        "tests/expected/one_two.through.out",
+    )
+}
+
+#[test]
+fn dies_bad_pattern() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["--until", "(", ONE])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("illegal pattern -- ("));
+
+    Ok(())
+}
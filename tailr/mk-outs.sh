@@ -0,0 +1,31 @@
+#!/usr/bin/env bash
+
+INPUTS="./tests/inputs"
+OUT_DIR="./tests/expected"
+
+[[ ! -d "$OUT_DIR" ]] && mkdir -p "$OUT_DIR"
+
+for FILE in $INPUTS/*.txt; do
+    BASENAME=$(basename "$FILE")
+    tail       $FILE > ${OUT_DIR}/${BASENAME}.out
+    tail -n 2  $FILE > ${OUT_DIR}/${BASENAME}.n2.out
+    tail -n 4  $FILE > ${OUT_DIR}/${BASENAME}.n4.out
+    tail -n +2 $FILE > ${OUT_DIR}/${BASENAME}.n+2.out
+    tail -c 1  $FILE > ${OUT_DIR}/${BASENAME}.c1.out
+    tail -c 4  $FILE > ${OUT_DIR}/${BASENAME}.c4.out
+    tail -c +3 $FILE > ${OUT_DIR}/${BASENAME}.c+3.out
+done
+
+ALL="$INPUTS/empty.txt $INPUTS/one.txt $INPUTS/two.txt $INPUTS/three.txt \
+    $INPUTS/ten.txt $INPUTS/unterminated.txt"
+tail         $ALL > $OUT_DIR/all.out
+tail -n 2    $ALL > $OUT_DIR/all.n2.out
+tail -n +2   $ALL > $OUT_DIR/all.n+2.out
+tail -c 4    $ALL > $OUT_DIR/all.c4.out
+tail -q -n 1 $ALL > $OUT_DIR/all.n1.q.out
+tail -v      $INPUTS/ten.txt > $OUT_DIR/ten.txt.v.out
+
+# 0 não mostra nada e +0 começa no início, como +1
+tail -n 0  $INPUTS/ten.txt > $OUT_DIR/ten.txt.n0.out
+tail -c 0  $INPUTS/ten.txt > $OUT_DIR/ten.txt.c0.out
+tail -n +0 $INPUTS/ten.txt > $OUT_DIR/ten.txt.n+0.out
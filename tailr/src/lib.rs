@@ -0,0 +1,370 @@
+use clap::{App, Arg};
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+
+type MyResult<T> = Result<T, Box<dyn Error>>;
+
+// tamanho dos blocos lidos do fim do arquivo ao procurar as últimas linhas
+const BLOCK_SIZE: usize = 64 * 1024;
+
+#[derive(Debug)]
+pub struct Config {
+    files: Vec<String>,
+    lines: Count,
+    bytes: Option<Count>,
+    quiet: bool,
+    verbose: bool,
+}
+
+// Quantidade de linhas ou bytes que serão mostrados. Por padrão são as
+// últimas K; com um "+" na frente (-n +5), a saída começa na linha ou byte
+// de número N e vai até o fim
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Count {
+    Last(usize),
+    From(usize),
+}
+
+// --------------------------------------------------
+pub fn get_args() -> MyResult<Config> {
+    let matches = App::new("tailr")
+        .version("0.1.0")
+        .author("Vitor Almeida")
+        .about("Versão do comando tail escrito em rust")
+        .arg(
+            Arg::with_name("lines")
+                .short("n")
+                .long("lines")
+                .value_name("LINES")
+                .help("Number of lines (+N: start at line N)")
+                .default_value("10") // por padrão mostra as 10 últimas linhas
+                .allow_hyphen_values(true),
+        )
+        .arg(
+            Arg::with_name("bytes")
+                .short("c")
+                .long("bytes")
+                .value_name("BYTES")
+                .takes_value(true)
+                .conflicts_with("lines") // linhas ou bytes, como no headr
+                .allow_hyphen_values(true)
+                .help("Number of bytes (+N: start at byte N)"),
+        )
+        .arg(
+            Arg::with_name("quiet")
+                .short("q")
+                .long("quiet")
+                .help("Never print headers giving file names")
+                .takes_value(false)
+                .conflicts_with("verbose"),
+        )
+        .arg(
+            Arg::with_name("verbose")
+                .short("v")
+                .long("verbose")
+                .help("Always print headers giving file names")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("files")
+                .value_name("FILE")
+                .help("Input file(s)")
+                .multiple(true)
+                .default_value("-"),
+        )
+        .get_matches();
+
+    let lines = matches
+        .value_of("lines")
+        .map(parse_count)
+        // o map retorna Option<Result>, transpose transforma em Result<Option>
+        .transpose()
+        .map_err(|e| format!("illegal line count -- {}", e))?;
+
+    let bytes = matches
+        .value_of("bytes")
+        .map(parse_count)
+        .transpose()
+        .map_err(|e| format!("illegal byte count -- {}", e))?;
+
+    Ok(Config {
+        files: matches.values_of_lossy("files").unwrap(),
+        // como lines possui um valor padrão, não corre o risco de panic
+        lines: lines.unwrap(),
+        bytes,
+        quiet: matches.is_present("quiet"),
+        verbose: matches.is_present("verbose"),
+    })
+}
+
+// --------------------------------------------------
+pub fn run(config: Config) -> MyResult<()> {
+    let num_files = config.files.len();
+    // como no headr, o cabeçalho só aparece com mais de um arquivo; -q e -v
+    // escolhem explicitamente
+    let show_headers = !config.quiet && (config.verbose || num_files > 1);
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+
+    for (file_num, filename) in config.files.iter().enumerate() {
+        match open(filename) {
+            Err(err) => eprintln!("{}: {}", filename, err),
+            Ok(input) => {
+                if show_headers {
+                    writeln!(
+                        out,
+                        "{}==> {} <==",
+                        // se for o primeiro arquivo, não pula linha
+                        if file_num > 0 { "\n" } else { "" },
+                        filename
+                    )?;
+                }
+                match input {
+                    Input::Seekable(file) => {
+                        tail_file(file, &config, &mut out)?
+                    }
+                    Input::Stream(file) => {
+                        tail_stream(file, &config, &mut out)?
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+// Arquivos regulares permitem pular direto para o trecho final; o stdin,
+// pipes e outros dispositivos precisam ser lidos do começo
+enum Input {
+    Seekable(File),
+    Stream(Box<dyn BufRead>),
+}
+
+fn open(filename: &str) -> MyResult<Input> {
+    if filename == "-" {
+        return Ok(Input::Stream(Box::new(BufReader::new(io::stdin()))));
+    }
+    // ? propaga o erro caso não consiga abrir
+    let file = File::open(filename)?;
+    if file.metadata()?.is_file() {
+        Ok(Input::Seekable(file))
+    } else {
+        Ok(Input::Stream(Box::new(BufReader::new(file))))
+    }
+}
+
+// --------------------------------------------------
+// Descobre onde começa o trecho que será mostrado e copia a partir dali, sem
+// ler o que vem antes. Só +N linhas exige ler o começo do arquivo, já que não
+// há como saber onde está a linha N sem contar as anteriores
+fn tail_file(
+    mut file: File,
+    config: &Config,
+    out: &mut impl Write,
+) -> MyResult<()> {
+    let len = file.metadata()?.len();
+    // arquivos como os do /proc informam tamanho 0, mas têm conteúdo
+    if len == 0 {
+        return tail_stream(BufReader::new(file), config, out);
+    }
+    let start = match (config.bytes, config.lines) {
+        (Some(Count::Last(num_bytes)), _) => {
+            len.saturating_sub(num_bytes as u64)
+        }
+        (Some(Count::From(num_bytes)), _) => (num_bytes as u64 - 1).min(len),
+        (None, Count::Last(num_lines)) => {
+            find_last_lines(&mut file, len, num_lines)?
+        }
+        (None, Count::From(_)) => {
+            return tail_stream(BufReader::new(file), config, out);
+        }
+    };
+    file.seek(SeekFrom::Start(start))?;
+    io::copy(&mut file, out)?;
+    Ok(())
+}
+
+// --------------------------------------------------
+// Lê o arquivo de trás para frente, em blocos, contando os \n até encontrar
+// o começo da K-ésima linha a partir do fim. O \n do último byte apenas
+// termina a última linha e não conta. Retorna o offset onde a saída começa
+fn find_last_lines(
+    file: &mut File,
+    len: u64,
+    num_lines: usize,
+) -> io::Result<u64> {
+    if num_lines == 0 {
+        return Ok(len);
+    }
+    let mut buffer = vec![0; BLOCK_SIZE];
+    let mut pos = len;
+    let mut newlines = 0;
+    while pos > 0 {
+        let size = (BLOCK_SIZE as u64).min(pos) as usize;
+        pos -= size as u64;
+        file.seek(SeekFrom::Start(pos))?;
+        file.read_exact(&mut buffer[..size])?;
+        for (i, &byte) in buffer[..size].iter().enumerate().rev() {
+            let offset = pos + i as u64;
+            if byte == b'\n' && offset + 1 < len {
+                newlines += 1;
+                if newlines == num_lines {
+                    return Ok(offset + 1);
+                }
+            }
+        }
+    }
+    // o arquivo tem menos linhas que o pedido
+    Ok(0)
+}
+
+// --------------------------------------------------
+// Entradas que só podem ser lidas do começo ao fim. As últimas K linhas ou
+// bytes ficam em um buffer circular, então a memória usada depende de K e
+// não do tamanho da entrada. O buffer cresce com a entrada em vez de ser
+// reservado de antemão, já que um K enorme é um jeito comum de pedir tudo
+fn tail_stream(
+    mut file: impl BufRead,
+    config: &Config,
+    out: &mut impl Write,
+) -> MyResult<()> {
+    match (config.bytes, config.lines) {
+        (Some(Count::Last(num_bytes)), _) => {
+            let mut last: VecDeque<u8> = VecDeque::new();
+            loop {
+                let chunk = file.fill_buf()?;
+                if chunk.is_empty() {
+                    break;
+                }
+                // o bloco é maior que o buffer inteiro, basta guardar o final
+                let keep = &chunk[chunk.len().saturating_sub(num_bytes)..];
+                let total = last.len() + keep.len();
+                last.drain(..total.saturating_sub(num_bytes));
+                last.extend(keep);
+                let len = chunk.len();
+                file.consume(len);
+            }
+            let (front, back) = last.as_slices();
+            out.write_all(front)?;
+            out.write_all(back)?;
+        }
+        (Some(Count::From(num_bytes)), _) => {
+            // descarta os bytes anteriores ao byte N
+            let mut skipped = file.by_ref().take(num_bytes as u64 - 1);
+            io::copy(&mut skipped, &mut io::sink())?;
+            io::copy(&mut file, out)?;
+        }
+        (None, Count::Last(0)) => {}
+        (None, Count::Last(num_lines)) => {
+            let mut last: VecDeque<Vec<u8>> = VecDeque::new();
+            loop {
+                // as linhas são lidas como bytes, já que vão apenas ser
+                // repassadas
+                let mut line = Vec::new();
+                if file.read_until(b'\n', &mut line)? == 0 {
+                    break;
+                }
+                if last.len() == num_lines {
+                    last.pop_front();
+                }
+                last.push_back(line);
+            }
+            for line in last {
+                out.write_all(&line)?;
+            }
+        }
+        (None, Count::From(num_lines)) => {
+            // descarta as linhas anteriores à linha N
+            let mut line = Vec::new();
+            for _ in 1..num_lines {
+                line.clear();
+                if file.read_until(b'\n', &mut line)? == 0 {
+                    break;
+                }
+            }
+            io::copy(&mut file, out)?;
+        }
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+// Um "+" na frente do número indica a linha ou byte onde a saída começa. Um
+// "-" é aceito e ignorado, como no tail do GNU (-n -5 é o mesmo que -n 5). O
+// erro continua sendo o valor original, para que a mensagem mostre exatamente
+// o que foi informado. Como no GNU, 0 não mostra nada e +0 é o mesmo que +1
+fn parse_count(val: &str) -> MyResult<Count> {
+    let count = if let Some(num) = val.strip_prefix('+') {
+        parse_int(num).map(|n| Count::From(n.max(1)))
+    } else {
+        let num = val.strip_prefix('-').unwrap_or(val);
+        parse_int(num).map(Count::Last)
+    };
+    count.map_err(|_| From::from(val))
+}
+
+// --------------------------------------------------
+// Cópia do parse_positive_int do headr, incluindo os sufixos multiplicadores
+// (1K, 10MiB...), já que os crates não compartilham código. A diferença é
+// que o tail aceita 0; mudanças nos sufixos devem ser feitas nos dois
+fn parse_int(val: &str) -> MyResult<usize> {
+    let num_len = val
+        .trim_end_matches(|c: char| c.is_ascii_alphabetic())
+        .len();
+    let (num, suffix) = val.split_at(num_len);
+    // checked_mul retorna None se a multiplicação não couber em um usize
+    let parsed = num
+        .parse::<usize>()
+        .ok()
+        .zip(parse_multiplier(suffix))
+        .and_then(|(n, multiplier)| n.checked_mul(multiplier));
+    parsed.ok_or_else(|| From::from(val))
+}
+
+// --------------------------------------------------
+// Também copiado do headr. Sufixos aceitos pelo GNU: b são blocos de 512
+// bytes, uma letra sozinha ou seguida de iB é uma potência de 1024 e a letra
+// seguida de B é uma potência de 1000
+fn parse_multiplier(suffix: &str) -> Option<usize> {
+    if suffix.is_empty() {
+        return Some(1);
+    }
+    if suffix == "b" {
+        return Some(512);
+    }
+    let mut chars = suffix.chars();
+    let exponent = match chars.next()? {
+        'K' | 'k' => 1,
+        'M' | 'm' => 2,
+        'G' => 3,
+        'T' => 4,
+        'P' => 5,
+        'E' => 6,
+        'Z' => 7,
+        'Y' => 8,
+        _ => return None,
+    };
+    let base: usize = match chars.as_str() {
+        "" | "iB" => 1024,
+        "B" => 1000,
+        _ => return None,
+    };
+    base.checked_pow(exponent)
+}
+
+#[test]
+fn test_parse_count() {
+    assert_eq!(parse_count("3").unwrap(), Count::Last(3));
+    assert_eq!(parse_count("-3").unwrap(), Count::Last(3));
+    assert_eq!(parse_count("+3").unwrap(), Count::From(3));
+    assert_eq!(parse_count("+1K").unwrap(), Count::From(1024));
+    let res = parse_count("+foo");
+    assert!(res.is_err());
+    assert_eq!(res.unwrap_err().to_string(), "+foo".to_string());
+    assert_eq!(parse_count("0").unwrap(), Count::Last(0));
+    assert_eq!(parse_count("+0").unwrap(), Count::From(1));
+    assert!(parse_count("+-3").is_err());
+}
@@ -0,0 +1,631 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use rand::{distributions::Alphanumeric, Rng};
+use std::error::Error;
+use std::fs;
+
+type TestResult = Result<(), Box<dyn Error>>;
+
+const PRG: &str = "tailr";
+const EMPTY: &str = "./tests/inputs/empty.txt";
+const ONE: &str = "./tests/inputs/one.txt";
+const TWO: &str = "./tests/inputs/two.txt";
+const THREE: &str = "./tests/inputs/three.txt";
+const TEN: &str = "./tests/inputs/ten.txt";
+const UNTERMINATED: &str = "./tests/inputs/unterminated.txt";
+
+// --------------------------------------------------
+fn random_string() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(7)
+        .map(char::from)
+        .collect()
+}
+
+// --------------------------------------------------
+fn gen_bad_file() -> String {
+    loop {
+        let filename = random_string();
+        if fs::metadata(&filename).is_err() {
+            return filename;
+        }
+    }
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_bytes() -> TestResult {
+    let bad = random_string();
+    let expected = format!("illegal byte count -- {}", &bad);
+    Command::cargo_bin(PRG)?
+        .args(["-c", &bad, EMPTY])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(expected));
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_lines() -> TestResult {
+    for bad in [random_string(), "+-1".to_string()] {
+        let expected = format!("illegal line count -- {}", &bad);
+        Command::cargo_bin(PRG)?
+            .args(["-n", &bad, EMPTY])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(expected));
+    }
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bytes_and_lines() -> TestResult {
+    let msg = "The argument '--lines <LINES>' cannot be \
+               used with '--bytes <BYTES>'";
+
+    Command::cargo_bin(PRG)?
+        .args(["-n", "1", "-c", "2"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(msg));
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn skips_bad_file() -> TestResult {
+    let bad = gen_bad_file();
+    let expected = format!("{}: .* [(]os error 2[)]", bad);
+    Command::cargo_bin(PRG)?
+        .args([EMPTY, &bad, ONE])
+        .assert()
+        .stderr(predicate::str::is_match(expected)?);
+
+    Ok(())
+}
+
+// --------------------------------------------------
+// os arquivos são comparados como bytes, já que -c pode cortar um
+// caractere utf8 no meio
+fn run(args: &[&str], expected_file: &str) -> TestResult {
+    let expected = fs::read(expected_file)?;
+    Command::cargo_bin(PRG)?
+        .args(args)
+        .assert()
+        .success()
+        .stdout(predicate::eq(expected.as_slice()));
+
+    Ok(())
+}
+
+// --------------------------------------------------
+// pelo stdin o tailr não pode usar seek e precisa do buffer circular
+fn run_stdin(
+    args: &[&str],
+    input_file: &str,
+    expected_file: &str,
+) -> TestResult {
+    let expected = fs::read(expected_file)?;
+    let input = fs::read(input_file)?;
+    Command::cargo_bin(PRG)?
+        .write_stdin(input)
+        .args(args)
+        .assert()
+        .success()
+        .stdout(predicate::eq(expected.as_slice()));
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn empty() -> TestResult {
+    run(&[EMPTY], "tests/expected/empty.txt.out")
+}
+
+#[test]
+fn empty_n2() -> TestResult {
+    run(&[EMPTY, "-n", "2"], "tests/expected/empty.txt.n2.out")
+}
+
+#[test]
+fn empty_n4() -> TestResult {
+    run(&[EMPTY, "-n", "4"], "tests/expected/empty.txt.n4.out")
+}
+
+#[test]
+fn empty_n_plus2() -> TestResult {
+    run(&[EMPTY, "-n", "+2"], "tests/expected/empty.txt.n+2.out")
+}
+
+#[test]
+fn empty_c1() -> TestResult {
+    run(&[EMPTY, "-c", "1"], "tests/expected/empty.txt.c1.out")
+}
+
+#[test]
+fn empty_c4() -> TestResult {
+    run(&[EMPTY, "-c", "4"], "tests/expected/empty.txt.c4.out")
+}
+
+#[test]
+fn empty_c_plus3() -> TestResult {
+    run(&[EMPTY, "-c", "+3"], "tests/expected/empty.txt.c+3.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn one() -> TestResult {
+    run(&[ONE], "tests/expected/one.txt.out")
+}
+
+#[test]
+fn one_n2() -> TestResult {
+    run(&[ONE, "-n", "2"], "tests/expected/one.txt.n2.out")
+}
+
+#[test]
+fn one_n4() -> TestResult {
+    run(&[ONE, "-n", "4"], "tests/expected/one.txt.n4.out")
+}
+
+#[test]
+fn one_n_plus2() -> TestResult {
+    run(&[ONE, "-n", "+2"], "tests/expected/one.txt.n+2.out")
+}
+
+#[test]
+fn one_c1() -> TestResult {
+    run(&[ONE, "-c", "1"], "tests/expected/one.txt.c1.out")
+}
+
+#[test]
+fn one_c4() -> TestResult {
+    run(&[ONE, "-c", "4"], "tests/expected/one.txt.c4.out")
+}
+
+#[test]
+fn one_c_plus3() -> TestResult {
+    run(&[ONE, "-c", "+3"], "tests/expected/one.txt.c+3.out")
+}
+
+#[test]
+fn one_stdin() -> TestResult {
+    run_stdin(&[], ONE, "tests/expected/one.txt.out")
+}
+
+#[test]
+fn one_n2_stdin() -> TestResult {
+    run_stdin(&["-n", "2"], ONE, "tests/expected/one.txt.n2.out")
+}
+
+#[test]
+fn one_n4_stdin() -> TestResult {
+    run_stdin(&["-n", "4"], ONE, "tests/expected/one.txt.n4.out")
+}
+
+#[test]
+fn one_n_plus2_stdin() -> TestResult {
+    run_stdin(&["-n", "+2"], ONE, "tests/expected/one.txt.n+2.out")
+}
+
+#[test]
+fn one_c1_stdin() -> TestResult {
+    run_stdin(&["-c", "1"], ONE, "tests/expected/one.txt.c1.out")
+}
+
+#[test]
+fn one_c4_stdin() -> TestResult {
+    run_stdin(&["-c", "4"], ONE, "tests/expected/one.txt.c4.out")
+}
+
+#[test]
+fn one_c_plus3_stdin() -> TestResult {
+    run_stdin(&["-c", "+3"], ONE, "tests/expected/one.txt.c+3.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn two() -> TestResult {
+    run(&[TWO], "tests/expected/two.txt.out")
+}
+
+#[test]
+fn two_n2() -> TestResult {
+    run(&[TWO, "-n", "2"], "tests/expected/two.txt.n2.out")
+}
+
+#[test]
+fn two_n4() -> TestResult {
+    run(&[TWO, "-n", "4"], "tests/expected/two.txt.n4.out")
+}
+
+#[test]
+fn two_n_plus2() -> TestResult {
+    run(&[TWO, "-n", "+2"], "tests/expected/two.txt.n+2.out")
+}
+
+#[test]
+fn two_c1() -> TestResult {
+    run(&[TWO, "-c", "1"], "tests/expected/two.txt.c1.out")
+}
+
+#[test]
+fn two_c4() -> TestResult {
+    run(&[TWO, "-c", "4"], "tests/expected/two.txt.c4.out")
+}
+
+#[test]
+fn two_c_plus3() -> TestResult {
+    run(&[TWO, "-c", "+3"], "tests/expected/two.txt.c+3.out")
+}
+
+#[test]
+fn two_stdin() -> TestResult {
+    run_stdin(&[], TWO, "tests/expected/two.txt.out")
+}
+
+#[test]
+fn two_n2_stdin() -> TestResult {
+    run_stdin(&["-n", "2"], TWO, "tests/expected/two.txt.n2.out")
+}
+
+#[test]
+fn two_n4_stdin() -> TestResult {
+    run_stdin(&["-n", "4"], TWO, "tests/expected/two.txt.n4.out")
+}
+
+#[test]
+fn two_n_plus2_stdin() -> TestResult {
+    run_stdin(&["-n", "+2"], TWO, "tests/expected/two.txt.n+2.out")
+}
+
+#[test]
+fn two_c1_stdin() -> TestResult {
+    run_stdin(&["-c", "1"], TWO, "tests/expected/two.txt.c1.out")
+}
+
+#[test]
+fn two_c4_stdin() -> TestResult {
+    run_stdin(&["-c", "4"], TWO, "tests/expected/two.txt.c4.out")
+}
+
+#[test]
+fn two_c_plus3_stdin() -> TestResult {
+    run_stdin(&["-c", "+3"], TWO, "tests/expected/two.txt.c+3.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn three() -> TestResult {
+    run(&[THREE], "tests/expected/three.txt.out")
+}
+
+#[test]
+fn three_n2() -> TestResult {
+    run(&[THREE, "-n", "2"], "tests/expected/three.txt.n2.out")
+}
+
+#[test]
+fn three_n4() -> TestResult {
+    run(&[THREE, "-n", "4"], "tests/expected/three.txt.n4.out")
+}
+
+#[test]
+fn three_n_plus2() -> TestResult {
+    run(&[THREE, "-n", "+2"], "tests/expected/three.txt.n+2.out")
+}
+
+#[test]
+fn three_c1() -> TestResult {
+    run(&[THREE, "-c", "1"], "tests/expected/three.txt.c1.out")
+}
+
+#[test]
+fn three_c4() -> TestResult {
+    run(&[THREE, "-c", "4"], "tests/expected/three.txt.c4.out")
+}
+
+#[test]
+fn three_c_plus3() -> TestResult {
+    run(&[THREE, "-c", "+3"], "tests/expected/three.txt.c+3.out")
+}
+
+#[test]
+fn three_stdin() -> TestResult {
+    run_stdin(&[], THREE, "tests/expected/three.txt.out")
+}
+
+#[test]
+fn three_n2_stdin() -> TestResult {
+    run_stdin(&["-n", "2"], THREE, "tests/expected/three.txt.n2.out")
+}
+
+#[test]
+fn three_n4_stdin() -> TestResult {
+    run_stdin(&["-n", "4"], THREE, "tests/expected/three.txt.n4.out")
+}
+
+#[test]
+fn three_n_plus2_stdin() -> TestResult {
+    run_stdin(&["-n", "+2"], THREE, "tests/expected/three.txt.n+2.out")
+}
+
+#[test]
+fn three_c1_stdin() -> TestResult {
+    run_stdin(&["-c", "1"], THREE, "tests/expected/three.txt.c1.out")
+}
+
+#[test]
+fn three_c4_stdin() -> TestResult {
+    run_stdin(&["-c", "4"], THREE, "tests/expected/three.txt.c4.out")
+}
+
+#[test]
+fn three_c_plus3_stdin() -> TestResult {
+    run_stdin(&["-c", "+3"], THREE, "tests/expected/three.txt.c+3.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn ten() -> TestResult {
+    run(&[TEN], "tests/expected/ten.txt.out")
+}
+
+#[test]
+fn ten_n2() -> TestResult {
+    run(&[TEN, "-n", "2"], "tests/expected/ten.txt.n2.out")
+}
+
+#[test]
+fn ten_n4() -> TestResult {
+    run(&[TEN, "-n", "4"], "tests/expected/ten.txt.n4.out")
+}
+
+#[test]
+fn ten_n_plus2() -> TestResult {
+    run(&[TEN, "-n", "+2"], "tests/expected/ten.txt.n+2.out")
+}
+
+#[test]
+fn ten_c1() -> TestResult {
+    run(&[TEN, "-c", "1"], "tests/expected/ten.txt.c1.out")
+}
+
+#[test]
+fn ten_c4() -> TestResult {
+    run(&[TEN, "-c", "4"], "tests/expected/ten.txt.c4.out")
+}
+
+#[test]
+fn ten_c_plus3() -> TestResult {
+    run(&[TEN, "-c", "+3"], "tests/expected/ten.txt.c+3.out")
+}
+
+#[test]
+fn ten_stdin() -> TestResult {
+    run_stdin(&[], TEN, "tests/expected/ten.txt.out")
+}
+
+#[test]
+fn ten_n2_stdin() -> TestResult {
+    run_stdin(&["-n", "2"], TEN, "tests/expected/ten.txt.n2.out")
+}
+
+#[test]
+fn ten_n4_stdin() -> TestResult {
+    run_stdin(&["-n", "4"], TEN, "tests/expected/ten.txt.n4.out")
+}
+
+#[test]
+fn ten_n_plus2_stdin() -> TestResult {
+    run_stdin(&["-n", "+2"], TEN, "tests/expected/ten.txt.n+2.out")
+}
+
+#[test]
+fn ten_c1_stdin() -> TestResult {
+    run_stdin(&["-c", "1"], TEN, "tests/expected/ten.txt.c1.out")
+}
+
+#[test]
+fn ten_c4_stdin() -> TestResult {
+    run_stdin(&["-c", "4"], TEN, "tests/expected/ten.txt.c4.out")
+}
+
+#[test]
+fn ten_c_plus3_stdin() -> TestResult {
+    run_stdin(&["-c", "+3"], TEN, "tests/expected/ten.txt.c+3.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn unterminated() -> TestResult {
+    run(&[UNTERMINATED], "tests/expected/unterminated.txt.out")
+}
+
+#[test]
+fn unterminated_n2() -> TestResult {
+    run(&[UNTERMINATED, "-n", "2"], "tests/expected/unterminated.txt.n2.out")
+}
+
+#[test]
+fn unterminated_n4() -> TestResult {
+    run(&[UNTERMINATED, "-n", "4"], "tests/expected/unterminated.txt.n4.out")
+}
+
+#[test]
+fn unterminated_n_plus2() -> TestResult {
+    run(&[UNTERMINATED, "-n", "+2"], "tests/expected/unterminated.txt.n+2.out")
+}
+
+#[test]
+fn unterminated_c1() -> TestResult {
+    run(&[UNTERMINATED, "-c", "1"], "tests/expected/unterminated.txt.c1.out")
+}
+
+#[test]
+fn unterminated_c4() -> TestResult {
+    run(&[UNTERMINATED, "-c", "4"], "tests/expected/unterminated.txt.c4.out")
+}
+
+#[test]
+fn unterminated_c_plus3() -> TestResult {
+    run(&[UNTERMINATED, "-c", "+3"], "tests/expected/unterminated.txt.c+3.out")
+}
+
+#[test]
+fn unterminated_stdin() -> TestResult {
+    run_stdin(&[], UNTERMINATED, "tests/expected/unterminated.txt.out")
+}
+
+#[test]
+fn unterminated_n2_stdin() -> TestResult {
+    run_stdin(
+        &["-n", "2"],
+        UNTERMINATED,
+        "tests/expected/unterminated.txt.n2.out",
+    )
+}
+
+#[test]
+fn unterminated_n4_stdin() -> TestResult {
+    run_stdin(
+        &["-n", "4"],
+        UNTERMINATED,
+        "tests/expected/unterminated.txt.n4.out",
+    )
+}
+
+#[test]
+fn unterminated_n_plus2_stdin() -> TestResult {
+    run_stdin(
+        &["-n", "+2"],
+        UNTERMINATED,
+        "tests/expected/unterminated.txt.n+2.out",
+    )
+}
+
+#[test]
+fn unterminated_c1_stdin() -> TestResult {
+    run_stdin(
+        &["-c", "1"],
+        UNTERMINATED,
+        "tests/expected/unterminated.txt.c1.out",
+    )
+}
+
+#[test]
+fn unterminated_c4_stdin() -> TestResult {
+    run_stdin(
+        &["-c", "4"],
+        UNTERMINATED,
+        "tests/expected/unterminated.txt.c4.out",
+    )
+}
+
+#[test]
+fn unterminated_c_plus3_stdin() -> TestResult {
+    run_stdin(
+        &["-c", "+3"],
+        UNTERMINATED,
+        "tests/expected/unterminated.txt.c+3.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn multiple_files() -> TestResult {
+    run(
+        &[EMPTY, ONE, TWO, THREE, TEN, UNTERMINATED],
+        "tests/expected/all.out",
+    )
+}
+
+#[test]
+fn multiple_files_n2() -> TestResult {
+    run(
+        &["-n", "2", EMPTY, ONE, TWO, THREE, TEN, UNTERMINATED],
+        "tests/expected/all.n2.out",
+    )
+}
+
+#[test]
+fn multiple_files_n_plus2() -> TestResult {
+    run(
+        &["-n", "+2", EMPTY, ONE, TWO, THREE, TEN, UNTERMINATED],
+        "tests/expected/all.n+2.out",
+    )
+}
+
+#[test]
+fn multiple_files_c4() -> TestResult {
+    run(
+        &["-c", "4", EMPTY, ONE, TWO, THREE, TEN, UNTERMINATED],
+        "tests/expected/all.c4.out",
+    )
+}
+
+#[test]
+fn multiple_files_quiet() -> TestResult {
+    run(
+        &["-q", "-n", "1", EMPTY, ONE, TWO, THREE, TEN, UNTERMINATED],
+        "tests/expected/all.n1.q.out",
+    )
+}
+
+#[test]
+fn ten_verbose() -> TestResult {
+    run(&["-v", TEN], "tests/expected/ten.txt.v.out")
+}
+
+#[test]
+fn ten_n0() -> TestResult {
+    run(&["-n", "0", TEN], "tests/expected/ten.txt.n0.out")
+}
+
+#[test]
+fn ten_n0_stdin() -> TestResult {
+    run_stdin(&["-n", "0"], TEN, "tests/expected/ten.txt.n0.out")
+}
+
+// um K maior que a entrada mostra tudo, sem reservar memória para K
+#[test]
+fn ten_n_huge_stdin() -> TestResult {
+    run_stdin(&["-n", "1T"], TEN, "tests/expected/ten.txt.out")
+}
+
+#[test]
+fn ten_c_huge_stdin() -> TestResult {
+    run_stdin(&["-c", "100000000000000"], TEN, "tests/expected/ten.txt.out")
+}
+
+#[test]
+fn ten_c0() -> TestResult {
+    run(&["-c", "0", TEN], "tests/expected/ten.txt.c0.out")
+}
+
+#[test]
+fn ten_n_plus0() -> TestResult {
+    run(&["-n", "+0", TEN], "tests/expected/ten.txt.n+0.out")
+}
+
+// --------------------------------------------------
+// os arquivos do /proc informam tamanho 0, então são lidos do começo
+#[cfg(target_os = "linux")]
+#[test]
+fn proc_file_last_line() -> TestResult {
+    let contents = fs::read_to_string("/proc/self/mountinfo")?;
+    let last = contents.lines().last().unwrap_or_default();
+    Command::cargo_bin(PRG)?
+        .args(["-n", "1", "/proc/self/mountinfo"])
+        .assert()
+        .success()
+        .stdout(format!("{}\n", last));
+    Ok(())
+}
@@ -0,0 +1,104 @@
+// Mede a vazão de wcr::count_with em uma entrada de vários GB. Por padrão a
+// entrada é um texto repetido até 2 GiB, gerado na memória; WCR_BENCH_GIB
+// muda o tamanho e WCR_BENCH_FILE usa um arquivo de verdade:
+//
+//     cargo bench --bench count
+//     WCR_BENCH_FILE=/caminho/grande.txt cargo bench --bench count
+use std::env;
+use std::fs::File;
+use std::hint::black_box;
+use std::io::{self, BufRead, BufReader, Read};
+use std::time::Instant;
+use wcr::{count_with, CountOptions};
+
+const SAMPLE: &str = "The quick brown fox\tjumps over the lazy dog.\n\
+                      Frétt hefir öld óvu, þá er endr of gerðu\n\
+                      日本語のテキストも少し混ぜておく\n\n";
+
+// --------------------------------------------------
+// Repete um bloco de 1 MiB com cópias de SAMPLE até completar o tamanho
+// pedido, sem alocar a entrada inteira. O bloco grande evita que as cópias
+// do próprio leitor pesem na medição
+struct Repeat {
+    block: Vec<u8>,
+    pos: usize,
+    remaining: u64,
+}
+
+impl Repeat {
+    fn new(len: u64) -> Self {
+        let copies = (1 << 20) / SAMPLE.len() + 1;
+        Repeat {
+            block: SAMPLE.repeat(copies).into_bytes(),
+            pos: 0,
+            remaining: len,
+        }
+    }
+}
+
+impl Read for Repeat {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut written = 0;
+        while written < buf.len() && self.remaining > 0 {
+            let chunk = self.fill_buf()?;
+            let len = chunk.len().min(buf.len() - written);
+            buf[written..written + len].copy_from_slice(&chunk[..len]);
+            self.consume(len);
+            written += len;
+        }
+        Ok(written)
+    }
+}
+
+impl BufRead for Repeat {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        let rest = &self.block[self.pos..];
+        Ok(&rest[..rest.len().min(self.remaining as usize)])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.pos = (self.pos + amt) % self.block.len();
+        self.remaining -= amt as u64;
+    }
+}
+
+// --------------------------------------------------
+fn input() -> io::Result<(Box<dyn BufRead>, u64)> {
+    if let Ok(path) = env::var("WCR_BENCH_FILE") {
+        let file = File::open(path)?;
+        let len = file.metadata()?.len();
+        return Ok((Box::new(BufReader::new(file)), len));
+    }
+    let gib: u64 = env::var("WCR_BENCH_GIB")
+        .ok()
+        .and_then(|val| val.parse().ok())
+        .unwrap_or(2);
+    let len = gib << 30;
+    Ok((Box::new(Repeat::new(len)), len))
+}
+
+// --------------------------------------------------
+fn main() -> io::Result<()> {
+    let modes = [
+        ("linhas, palavras e bytes", false, false),
+        ("com caracteres (-m)", true, false),
+        ("com larguras (-L)", false, true),
+    ];
+    for (name, chars, line_widths) in modes {
+        let (file, len) = input()?;
+        let options = CountOptions { chars, line_widths };
+        let start = Instant::now();
+        let info = count_with(file, options).expect("falha na contagem");
+        let secs = start.elapsed().as_secs_f64();
+        black_box(info);
+        let gib = len as f64 / (1u64 << 30) as f64;
+        println!(
+            "{:<26} {:.2} GiB em {:.2}s: {:.2} GiB/s",
+            name,
+            gib,
+            secs,
+            gib / secs
+        );
+    }
+    Ok(())
+}
@@ -1,5 +1,8 @@
 #!/usr/bin/env bash
 
+# -m e -L dependem do locale, então as saídas usam sempre UTF-8
+export LC_ALL=C.UTF-8
+
 ROOT="tests/inputs"
 FILES="$ROOT/empty.txt $ROOT/fox.txt $ROOT/atlamal.txt"
 OUT_DIR="tests/expected"
@@ -21,7 +24,9 @@ for FILE in $FILES; do
     wc -ml  $FILE > ${OUT_DIR}/${BASENAME}.ml.out
 done
 
+# com o arquivo no stdin, a largura vem do tamanho dele; com um pipe, é 7
 wc < "$ROOT/atlamal.txt" > "$OUT_DIR/atlamal.txt.stdin.out"
+cat "$ROOT/atlamal.txt" | wc > "$OUT_DIR/atlamal.txt.pipe.out"
 
 wc      $FILES > $OUT_DIR/all.out
 wc -l   $FILES > $OUT_DIR/all.l.out
@@ -34,3 +39,28 @@ wc -wm  $FILES > $OUT_DIR/all.wm.out
 wc -wl  $FILES > $OUT_DIR/all.wl.out
 wc -cl  $FILES > $OUT_DIR/all.cl.out
 wc -ml  $FILES > $OUT_DIR/all.ml.out
+
+# bytes inválidos não são caracteres e não formam palavras
+INVALID="$ROOT/invalid.txt"
+wc $INVALID > $OUT_DIR/invalid.txt.out
+wc -lwm $INVALID > $OUT_DIR/invalid.txt.lwm.out
+
+# -L considera a largura dos caracteres na tela
+WIDE="$ROOT/wide.txt"
+wc -L $ROOT/fox.txt > $OUT_DIR/fox.txt.L.out
+wc -L $WIDE > $OUT_DIR/wide.txt.L.out
+wc -L $FILES $WIDE > $OUT_DIR/all.L.out
+
+# --stats não existe no wc: wide.txt.stats.out e all.l.stats.out foram
+# conferidos à mão
+
+# wc 9.1 não tem --total: as saídas de --total foram conferidas à mão
+
+# os nomes vêm de uma lista; com um pipe, a lista é lida aos poucos e as
+# colunas não são alinhadas. --files-from não existe no wc, mas a lista com
+# quebras de linha tem os mesmos nomes e usa files0-from.out
+LIST="$ROOT/files0-from.list"
+wc --files0-from=$LIST > $OUT_DIR/files0-from.out
+cat $LIST | wc --files0-from=- > $OUT_DIR/files0-from.stdin.out
+
+# --freq não existe no wc: as saídas freq*.out foram conferidas à mão
@@ -0,0 +1,417 @@
+use super::{FileInfo, MyResult};
+use memchr::{memchr3_iter, memchr_iter};
+use std::io::{BufRead, ErrorKind};
+use unicode_width::UnicodeWidthChar;
+
+// tamanho dos blocos lidos de cada arquivo
+const BUF_SIZE: usize = 256 * 1024;
+
+// Medidas que exigem decodificar o UTF-8. Linhas, palavras e bytes saem
+// direto dos bytes e são sempre contados; caracteres e larguras de linha só
+// são calculados quando pedidos, já que decodificar é a parte mais lenta
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CountOptions {
+    pub chars: bool,
+    pub line_widths: bool,
+}
+
+// --------------------------------------------------
+// Conta tudo, incluindo caracteres e larguras de linha
+pub fn count(file: impl BufRead) -> MyResult<FileInfo> {
+    count_with(
+        file,
+        CountOptions {
+            chars: true,
+            line_widths: true,
+        },
+    )
+}
+
+// --------------------------------------------------
+pub fn count_with(
+    file: impl BufRead,
+    options: CountOptions,
+) -> MyResult<FileInfo> {
+    let mut counter = Counter::new(options);
+    read_blocks(file, |block| counter.update(block))?;
+    Ok(counter.finish())
+}
+
+// --------------------------------------------------
+// Lê o arquivo em blocos grandes, sem separar as linhas. Um caractere UTF-8
+// pode ficar dividido entre dois blocos, então os bytes de uma sequência
+// incompleta no fim do bloco são movidos para o começo do buffer e entregues
+// junto com o bloco seguinte
+pub(crate) fn read_blocks(
+    mut file: impl BufRead,
+    mut process: impl FnMut(&[u8]),
+) -> MyResult<()> {
+    let mut buffer = vec![0; BUF_SIZE];
+    let mut kept = 0;
+    loop {
+        let len = match file.read(&mut buffer[kept..]) {
+            Ok(0) => break,
+            Ok(n) => kept + n,
+            Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err.into()),
+        };
+        let end = len - incomplete_tail(&buffer[..len]);
+        process(&buffer[..end]);
+        buffer.copy_within(end..len, 0);
+        kept = len - end;
+    }
+    // o que sobrou no fim do arquivo é uma sequência inválida
+    process(&buffer[..kept]);
+    Ok(())
+}
+
+// --------------------------------------------------
+// Quantos bytes no fim do bloco começam um caractere que não terminou
+fn incomplete_tail(bytes: &[u8]) -> usize {
+    // um caractere tem no máximo 4 bytes, então basta olhar os 3 últimos
+    for back in 1..=bytes.len().min(3) {
+        let byte = bytes[bytes.len() - back];
+        if !is_continuation(byte) {
+            return if sequence_len(byte) > back { back } else { 0 };
+        }
+    }
+    0
+}
+
+fn is_continuation(byte: u8) -> bool {
+    byte & 0xC0 == 0x80
+}
+
+// tamanho da sequência indicado pelo primeiro byte. Bytes que não podem
+// começar um caractere valem 1, assim são tratados como inválidos
+fn sequence_len(byte: u8) -> usize {
+    match byte {
+        0xC2..=0xDF => 2,
+        0xE0..=0xEF => 3,
+        0xF0..=0xF4 => 4,
+        _ => 1,
+    }
+}
+
+// --------------------------------------------------
+// Papel de um caractere na separação das palavras, como no wc do GNU: espaços
+// separam, caracteres visíveis formam as palavras e os demais (caracteres de
+// controle e bytes inválidos) não começam nem terminam uma palavra
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Class {
+    Space,
+    Word,
+    Other,
+}
+
+// Classifica o caractere no começo de bytes e retorna quantos bytes ele ocupa.
+// Os espaços são os de char::is_whitespace, menos U+0085, que é um caractere
+// de controle. Sequências UTF-8 são reconhecidas sem decodificar o caractere,
+// apenas conferindo os bytes de continuação
+pub(crate) fn classify(bytes: &[u8]) -> (Class, usize) {
+    match *bytes {
+        [b' ' | b'\t' | b'\n' | b'\x0b' | b'\x0c' | b'\r', ..] => {
+            (Class::Space, 1)
+        }
+        [b'!'..=b'~', ..] => (Class::Word, 1),
+        [0x00..=0x7F, ..] => (Class::Other, 1),
+        // U+00A0
+        [0xC2, 0xA0, ..] => (Class::Space, 2),
+        // U+0080 a U+009F são caracteres de controle
+        [0xC2, 0x80..=0x9F, ..] => (Class::Other, 2),
+        // U+1680
+        [0xE1, 0x9A, 0x80, ..] => (Class::Space, 3),
+        // U+2000 a U+200A, U+2028, U+2029 e U+202F
+        [0xE2, 0x80, 0x80..=0x8A | 0xA8 | 0xA9 | 0xAF, ..] => (Class::Space, 3),
+        // U+205F
+        [0xE2, 0x81, 0x9F, ..] => (Class::Space, 3),
+        // U+3000
+        [0xE3, 0x80, 0x80, ..] => (Class::Space, 3),
+        [lead, ..] => {
+            let len = sequence_len(lead);
+            let complete = len > 1
+                && bytes.len() >= len
+                && bytes[1..len].iter().all(|&b| is_continuation(b));
+            if complete {
+                (Class::Word, len)
+            } else {
+                (Class::Other, 1)
+            }
+        }
+        [] => (Class::Other, 0),
+    }
+}
+
+// --------------------------------------------------
+// Tabela da contagem rápida de palavras. O bit 1 indica um byte que não é
+// espaço ASCII. O bit 2 marca os caracteres de controle ASCII, que exigem
+// classify, e o bit 4 os primeiros bytes que podem começar um espaço ou
+// controle fora do ASCII; eles também começam letras comuns (como os
+// hiraganas, com 0xE3), então só são conferidos se aparecerem. Em um texto
+// válido, os bytes de continuação seguem um primeiro byte que também tem o
+// bit 1, então não mudam nada
+const FAST_CLASS: [u8; 256] = {
+    let mut table = [1; 256];
+    let mut byte = 0;
+    while byte < 256 {
+        table[byte] = match byte as u8 {
+            b' ' | b'\t' | b'\n' | b'\x0b' | b'\x0c' | b'\r' => 0,
+            0x00..=0x1F | 0x7F => 2,
+            0xC2 | 0xE1 | 0xE2 | 0xE3 => 1 | 4,
+            _ => 1,
+        };
+        byte += 1;
+    }
+    table
+};
+
+// tamanho dos trechos da contagem rápida de palavras
+const WORD_BLOCK: usize = 4096;
+
+// --------------------------------------------------
+// Estado da contagem entre um bloco e outro
+struct Counter {
+    options: CountOptions,
+    info: FileInfo,
+    // o último byte lido faz parte de uma palavra
+    in_word: bool,
+    // coluna atual e maior coluna da linha, para -L e --stats
+    pos: usize,
+    line_max: usize,
+    // há bytes depois do último \n, ou seja, uma linha sem terminador
+    line_open: bool,
+}
+
+impl Counter {
+    fn new(options: CountOptions) -> Self {
+        Counter {
+            options,
+            info: FileInfo::default(),
+            in_word: false,
+            pos: 0,
+            line_max: 0,
+            line_open: false,
+        }
+    }
+
+    fn update(&mut self, bytes: &[u8]) {
+        self.info.num_bytes += bytes.len();
+        // memchr procura os \n com instruções SIMD
+        self.info.num_lines += memchr_iter(b'\n', bytes).count();
+        self.count_words(bytes);
+        if self.options.chars || self.options.line_widths {
+            self.decode(bytes);
+        }
+    }
+
+    // Uma palavra começa em cada caractere visível que vem depois de um
+    // espaço (ou do começo do arquivo). Bytes inválidos não mudam nada, então
+    // basta contar os trechos válidos separados por utf8_chunks
+    fn count_words(&mut self, bytes: &[u8]) {
+        for chunk in bytes.utf8_chunks() {
+            let text = chunk.valid().as_bytes();
+            let mut start = 0;
+            while start < text.len() {
+                let mut end = (start + WORD_BLOCK).min(text.len());
+                // o trecho não pode terminar no meio de um caractere
+                while end < text.len() && is_continuation(text[end]) {
+                    end += 1;
+                }
+                if !self.count_words_fast(&text[start..end]) {
+                    self.count_words_exact(text, start, end);
+                }
+                start = end;
+            }
+        }
+    }
+
+    // Na maioria dos textos, cada byte é só espaço ou não espaço, e uma
+    // palavra começa onde um não espaço segue um espaço. Sem desvios, o laço
+    // é vetorizado pelo compilador. Retorna false, sem alterar a contagem, se
+    // o trecho tiver algum caractere que não seja espaço ASCII nem letra
+    fn count_words_fast(&mut self, text: &[u8]) -> bool {
+        let mut words = 0;
+        let mut prev = self.in_word as usize;
+        let mut special = 0;
+        for &byte in text {
+            let class = FAST_CLASS[byte as usize] as usize;
+            special |= class;
+            let word = class & 1;
+            words += word & (prev ^ 1);
+            prev = word;
+        }
+        if special & 2 != 0 {
+            return false;
+        }
+        if special & 4 != 0 {
+            let mut leads = memchr3_iter(0xE1, 0xE2, 0xE3, text)
+                .chain(memchr_iter(0xC2, text));
+            if leads.any(|i| classify(&text[i..]).0 != Class::Word) {
+                return false;
+            }
+        }
+        self.info.num_words += words;
+        self.in_word = prev == 1;
+        true
+    }
+
+    // Caractere a caractere, consultando classify. text é o trecho válido
+    // inteiro, para que classify enxergue os bytes depois de end
+    fn count_words_exact(&mut self, text: &[u8], start: usize, end: usize) {
+        let mut i = start;
+        while i < end {
+            let (class, len) = classify(&text[i..]);
+            match class {
+                Class::Space => self.in_word = false,
+                Class::Word => {
+                    self.info.num_words += !self.in_word as usize;
+                    self.in_word = true;
+                }
+                Class::Other => {}
+            }
+            i += len;
+        }
+    }
+
+    // Como no wc do GNU, bytes inválidos não contam como caracteres e não
+    // ocupam colunas
+    fn decode(&mut self, bytes: &[u8]) {
+        for chunk in bytes.utf8_chunks() {
+            let valid = chunk.valid();
+            if self.options.chars {
+                self.info.num_chars += valid.chars().count();
+            }
+            if self.options.line_widths {
+                valid.chars().for_each(|c| self.push_width(c));
+                if !chunk.invalid().is_empty() {
+                    self.line_open = true;
+                }
+            }
+        }
+    }
+
+    // Largura da linha na tela, calculada como no wc do GNU: tabs avançam até
+    // a próxima coluna múltipla de 8, caracteres largos (como os ideogramas)
+    // ocupam duas colunas e caracteres de controle ou combinantes não ocupam
+    // nenhuma. \r e \f voltam ao início da linha, então vale o maior trecho
+    // entre eles
+    fn push_width(&mut self, c: char) {
+        match c {
+            '\n' => self.end_line(),
+            '\t' => self.pos += 8 - self.pos % 8,
+            '\r' | '\x0c' => {
+                self.line_max = self.line_max.max(self.pos);
+                self.pos = 0;
+            }
+            // width retorna None para caracteres de controle
+            _ => self.pos += c.width().unwrap_or(0),
+        }
+        if c != '\n' {
+            self.line_open = true;
+        }
+    }
+
+    fn end_line(&mut self) {
+        let width = self.line_max.max(self.pos);
+        self.info.max_line_length = self.info.max_line_length.max(width);
+        self.info.line_lengths.record(width);
+        self.pos = 0;
+        self.line_max = 0;
+        self.line_open = false;
+    }
+
+    fn finish(mut self) -> FileInfo {
+        // a última linha sem \n também entra nas estatísticas
+        if self.line_open {
+            self.end_line();
+        }
+        self.info
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{count, count_with, incomplete_tail, CountOptions};
+    use std::io::{self, BufRead, Cursor, Read};
+
+    // leitor que devolve um byte por vez, dividindo os caracteres entre
+    // leituras
+    struct Trickle<'a>(&'a [u8]);
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let len = self.0.len().min(buf.len()).min(1);
+            buf[..len].copy_from_slice(&self.0[..len]);
+            self.0 = &self.0[len..];
+            Ok(len)
+        }
+    }
+
+    impl BufRead for Trickle<'_> {
+        fn fill_buf(&mut self) -> io::Result<&[u8]> {
+            Ok(&self.0[..self.0.len().min(1)])
+        }
+
+        fn consume(&mut self, amt: usize) {
+            self.0 = &self.0[amt..];
+        }
+    }
+
+    #[test]
+    fn test_incomplete_tail() {
+        assert_eq!(incomplete_tail(b""), 0);
+        assert_eq!(incomplete_tail(b"abc"), 0);
+        assert_eq!(incomplete_tail("日".as_bytes()), 0);
+        assert_eq!(incomplete_tail(&"日".as_bytes()[..2]), 2);
+        assert_eq!(incomplete_tail(b"a\xe6"), 1);
+        assert_eq!(incomplete_tail(b"a\xff"), 0);
+    }
+
+    #[test]
+    fn test_count_invalid() {
+        // como no GNU, os bytes inválidos não são caracteres e não começam
+        // nem terminam palavras
+        let info = count(Cursor::new(b"a\xff\xfeb \xe6\x97\n")).unwrap();
+        assert_eq!(info.num_lines, 1);
+        assert_eq!(info.num_words, 1);
+        assert_eq!(info.num_bytes, 8);
+        assert_eq!(info.num_chars, 4);
+    }
+
+    #[test]
+    fn test_count_separators() {
+        let text = "a\u{a0}b\u{3000}c d\u{200b}e\u{85}f \x01 \u{301}\n";
+        let info = count(Cursor::new(text)).unwrap();
+        // U+200B e U+0085 não são espaços, então d, e e f formam uma palavra
+        // só; \x01 não forma uma palavra, mas o acento combinante sim
+        assert_eq!(info.num_words, 5);
+
+        // textos maiores que WORD_BLOCK passam pela contagem rápida
+        let text = "ab\u{a0}\u{3042}\u{3042} ".repeat(3000);
+        let info = count(Cursor::new(text)).unwrap();
+        assert_eq!(info.num_words, 6000);
+    }
+
+    #[test]
+    fn test_count_split_chars() {
+        let info = count(Trickle("日本 語\tx\nab".as_bytes())).unwrap();
+        assert_eq!(info.num_lines, 1);
+        assert_eq!(info.num_words, 4);
+        assert_eq!(info.num_chars, 9);
+        assert_eq!(info.max_line_length, 9);
+        // a última linha, sem \n, entra nas estatísticas
+        assert_eq!(info.line_lengths.min(), 2);
+    }
+
+    #[test]
+    fn test_count_without_decoding() {
+        let options = CountOptions {
+            chars: false,
+            line_widths: false,
+        };
+        let info = count_with(Cursor::new("日本\n"), options).unwrap();
+        assert_eq!(info.num_bytes, 7);
+        assert_eq!(info.num_chars, 0);
+        assert_eq!(info.max_line_length, 0);
+    }
+}
@@ -0,0 +1,173 @@
+use super::{stdin_metadata, MyResult};
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Read};
+use std::vec;
+
+// Listas regulares até este tamanho são lidas de uma vez, como no wc do GNU,
+// para que a largura das colunas venha do tamanho dos arquivos. As outras,
+// como um pipe com a saída do findr, são lidas aos poucos, e as colunas não
+// são alinhadas
+const MAX_PRELOAD: u64 = 10 * 1024 * 1024;
+
+// Arquivo com os nomes dos arquivos a contar, de --files0-from ou
+// --files-from. O separador é o NUL ou a quebra de linha
+#[derive(Debug)]
+pub struct FileList {
+    pub path: String,
+    pub separator: u8,
+}
+
+// --------------------------------------------------
+// Nomes dos arquivos a contar, na ordem em que aparecem. Os nomes vêm dos
+// argumentos ou de uma lista, e só nesse caso são validados. Os nomes de uma
+// lista podem não ser UTF-8, então ficam como OsString até abrir os arquivos
+pub(crate) struct FileNames {
+    source: Source,
+    // nome da lista, para as mensagens de erro
+    list: Option<String>,
+    // número do nome atual na lista, a partir de 1
+    index: usize,
+}
+
+enum Source {
+    Loaded(vec::IntoIter<OsString>),
+    Stream {
+        reader: Box<dyn BufRead>,
+        separator: u8,
+    },
+}
+
+impl FileNames {
+    pub fn args(files: Vec<String>) -> Self {
+        let files: Vec<_> = files.into_iter().map(OsString::from).collect();
+        FileNames {
+            source: Source::Loaded(files.into_iter()),
+            list: None,
+            index: 0,
+        }
+    }
+
+    pub fn from_list(list: &FileList) -> MyResult<Self> {
+        let (metadata, reader): (_, Box<dyn Read>) = if list.path == "-" {
+            (stdin_metadata().ok(), Box::new(io::stdin()))
+        } else {
+            let file = File::open(&list.path).map_err(|e| {
+                format!("cannot open {} for reading: {}", list.path, e)
+            })?;
+            (fs::metadata(&list.path).ok(), Box::new(file))
+        };
+        let small = metadata
+            .is_some_and(|m| m.is_file() && m.len() <= MAX_PRELOAD);
+
+        let mut reader = BufReader::new(reader);
+        let source = if small {
+            let mut names = vec![];
+            while let Some(name) = read_name(&mut reader, list.separator) {
+                let name = name.map_err(|e| format!("{}: {}", list.path, e))?;
+                names.push(name);
+            }
+            Source::Loaded(names.into_iter())
+        } else {
+            Source::Stream {
+                reader: Box::new(reader),
+                separator: list.separator,
+            }
+        };
+        Ok(FileNames {
+            source,
+            list: Some(list.path.clone()),
+            index: 0,
+        })
+    }
+
+    // todos os nomes, quando já estão na memória
+    pub fn loaded(&self) -> Option<&[OsString]> {
+        match &self.source {
+            Source::Loaded(names) => Some(names.as_slice()),
+            Source::Stream { .. } => None,
+        }
+    }
+
+    // quantidade de nomes lidos até agora
+    pub fn seen(&self) -> usize {
+        self.index
+    }
+}
+
+impl Iterator for FileNames {
+    type Item = MyResult<OsString>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let name = match &mut self.source {
+                Source::Loaded(names) => names.next()?,
+                Source::Stream { reader, separator } => {
+                    match read_name(reader, *separator)? {
+                        Ok(name) => name,
+                        Err(e) => {
+                            let list = self.list.as_deref().unwrap_or("-");
+                            let error = format!("{}: {}", list, e);
+                            return Some(Err(error.into()));
+                        }
+                    }
+                }
+            };
+            self.index += 1;
+
+            // como no wc do GNU, nomes inválidos são avisados e ignorados
+            if let Some(list) = &self.list {
+                if name.is_empty() {
+                    eprintln!(
+                        "{}:{}: invalid zero-length file name",
+                        list, self.index
+                    );
+                    continue;
+                }
+                if name == "-" && list == "-" {
+                    eprintln!(
+                        "{}:{}: when reading file names from stdin, \
+                         no file name of '-' allowed",
+                        list, self.index
+                    );
+                    continue;
+                }
+            }
+            return Some(Ok(name));
+        }
+    }
+}
+
+// --------------------------------------------------
+// Lê um nome até o separador, que pode faltar no último
+fn read_name(
+    reader: &mut impl BufRead,
+    separator: u8,
+) -> Option<io::Result<OsString>> {
+    let mut buf = vec![];
+    match reader.read_until(separator, &mut buf) {
+        Ok(0) => None,
+        Ok(_) => {
+            if buf.last() == Some(&separator) {
+                buf.pop();
+            }
+            Some(Ok(name_from_bytes(buf)))
+        }
+        Err(e) => Some(Err(e)),
+    }
+}
+
+// --------------------------------------------------
+// No unix, um nome de arquivo é uma sequência de bytes qualquer, como os que
+// o findr -print0 escreve. Nos outros sistemas, os bytes inválidos são
+// trocados por U+FFFD
+#[cfg(unix)]
+fn name_from_bytes(bytes: Vec<u8>) -> OsString {
+    use std::os::unix::ffi::OsStringExt;
+    OsString::from_vec(bytes)
+}
+
+#[cfg(not(unix))]
+fn name_from_bytes(bytes: Vec<u8>) -> OsString {
+    String::from_utf8_lossy(&bytes).into_owned().into()
+}
@@ -0,0 +1,229 @@
+use super::count::{classify, read_blocks, Class};
+use super::{open, Format, MyResult};
+use serde::Serialize;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsString;
+use std::fs;
+use std::io::{self, Write};
+
+// Opções de --freq
+#[derive(Debug)]
+pub struct FreqOptions {
+    // quantas palavras mostrar, das mais frequentes
+    pub top: Option<usize>,
+    pub ignore_case: bool,
+    // tamanho mínimo das palavras, em caracteres
+    pub min_length: usize,
+    // arquivo com palavras que não entram na tabela
+    pub stopwords: Option<String>,
+}
+
+// Uma linha da tabela nos formatos estruturados
+#[derive(Debug, Serialize)]
+struct Entry<'a> {
+    word: &'a str,
+    count: usize,
+}
+
+// --------------------------------------------------
+// Monta uma única tabela com as palavras de todos os arquivos e mostra as
+// mais frequentes primeiro. Empates são ordenados pela palavra, para que a
+// saída não dependa da ordem dos arquivos
+pub(crate) fn run_freq(
+    names: impl Iterator<Item = MyResult<OsString>>,
+    options: &FreqOptions,
+    format: Format,
+) -> MyResult<()> {
+    let fold = |word: &str| {
+        if options.ignore_case {
+            word.to_lowercase()
+        } else {
+            word.to_string()
+        }
+    };
+    let stopwords = match &options.stopwords {
+        Some(path) => fs::read_to_string(path)
+            .map_err(|e| format!("{}: {}", path, e))?
+            .split_whitespace()
+            .map(fold)
+            .collect(),
+        None => HashSet::new(),
+    };
+
+    let mut table = Frequencies::new(options.ignore_case);
+    for filename in names {
+        let filename = filename?;
+        match open(&filename) {
+            Err(err) => eprintln!("{}: {}", filename.to_string_lossy(), err),
+            // as palavras lidas antes de um erro continuam na tabela
+            Ok(file) => {
+                if let Err(err) = table.read(file) {
+                    eprintln!("{}: {}", filename.to_string_lossy(), err);
+                }
+            }
+        }
+    }
+
+    let mut entries: Vec<_> = table
+        .counts
+        .iter()
+        .filter(|(word, _)| word.chars().count() >= options.min_length)
+        .filter(|(word, _)| !stopwords.contains(word.as_str()))
+        .map(|(word, &count)| Entry { word, count })
+        .collect();
+    entries.sort_unstable_by(|a, b| {
+        b.count.cmp(&a.count).then_with(|| a.word.cmp(b.word))
+    });
+    if let Some(top) = options.top {
+        entries.truncate(top);
+    }
+    write_entries(&entries, format)
+}
+
+// --------------------------------------------------
+// Quantas vezes cada palavra aparece. Com --ignore-case, as palavras são
+// guardadas em minúsculas
+struct Frequencies {
+    counts: HashMap<String, usize>,
+    ignore_case: bool,
+}
+
+impl Frequencies {
+    fn new(ignore_case: bool) -> Self {
+        Frequencies {
+            counts: HashMap::new(),
+            ignore_case,
+        }
+    }
+
+    fn read(&mut self, file: impl io::BufRead) -> MyResult<()> {
+        let mut words = Words::default();
+        read_blocks(file, |block| {
+            words.update(block, |word| self.add(word))
+        })?;
+        words.finish(|word| self.add(word));
+        Ok(())
+    }
+
+    // só aloca uma String para palavras que ainda não estão na tabela
+    fn add(&mut self, word: &[u8]) {
+        let word = String::from_utf8_lossy(word);
+        let word = if self.ignore_case {
+            Cow::Owned(word.to_lowercase())
+        } else {
+            word
+        };
+        match self.counts.get_mut(word.as_ref()) {
+            Some(count) => *count += 1,
+            None => {
+                self.counts.insert(word.into_owned(), 1);
+            }
+        }
+    }
+}
+
+// --------------------------------------------------
+// Separa as palavras com as mesmas regras de -w, então a soma da tabela é a
+// contagem de palavras do arquivo. Como na contagem, caracteres de controle e
+// bytes inválidos não começam nem terminam uma palavra, e também não fazem
+// parte do texto dela. A pontuação faz parte da palavra, como no wc
+#[derive(Debug, Default)]
+struct Words {
+    // bytes da palavra atual, que pode continuar no bloco seguinte
+    word: Vec<u8>,
+}
+
+impl Words {
+    fn update(&mut self, bytes: &[u8], mut emit: impl FnMut(&[u8])) {
+        let mut i = 0;
+        while i < bytes.len() {
+            let (class, len) = classify(&bytes[i..]);
+            match class {
+                Class::Space => self.finish(&mut emit),
+                Class::Word => {
+                    self.word.extend_from_slice(&bytes[i..i + len])
+                }
+                Class::Other => {}
+            }
+            i += len;
+        }
+    }
+
+    fn finish(&mut self, mut emit: impl FnMut(&[u8])) {
+        if !self.word.is_empty() {
+            emit(&self.word);
+            self.word.clear();
+        }
+    }
+}
+
+// --------------------------------------------------
+// Na tabela de texto, as contagens ficam alinhadas à direita, como no uniq -c
+fn write_entries(entries: &[Entry], format: Format) -> MyResult<()> {
+    let mut out = io::stdout().lock();
+    match format {
+        Format::Text => {
+            let width = entries
+                .first()
+                .map_or(1, |entry| entry.count.to_string().len());
+            for entry in entries {
+                writeln!(out, "{:>2$} {}", entry.count, entry.word, width)?;
+            }
+        }
+        Format::Json => {
+            if entries.is_empty() {
+                writeln!(out, "[]")?;
+            } else {
+                for (i, entry) in entries.iter().enumerate() {
+                    let separator = if i == 0 { "[\n" } else { ",\n" };
+                    let json = serde_json::to_string(entry)?;
+                    write!(out, "{}  {}", separator, json)?;
+                }
+                writeln!(out, "\n]")?;
+            }
+        }
+        Format::Csv | Format::Tsv => {
+            let delimiter = if format == Format::Csv { b',' } else { b'\t' };
+            let mut writer = csv::WriterBuilder::new()
+                .delimiter(delimiter)
+                .from_writer(out);
+            for entry in entries {
+                writer.serialize(entry)?;
+            }
+            writer.flush()?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Frequencies, Words};
+    use std::io::Cursor;
+
+    #[test]
+    fn test_words() {
+        let mut words = Words::default();
+        let mut found = vec![];
+        // uma palavra pode continuar no bloco seguinte
+        let blocks: [&[u8]; 3] =
+            ["The fox\u{a0}jum".as_bytes(), b"p\x01ed\xff  o", b"ver"];
+        for block in blocks {
+            words.update(block, |word| found.push(word.to_vec()));
+        }
+        words.finish(|word| found.push(word.to_vec()));
+        let expected: [&[u8]; 4] = [b"The", b"fox", b"jumped", b"over"];
+        assert_eq!(found, expected);
+    }
+
+    #[test]
+    fn test_frequencies() {
+        let mut table = Frequencies::new(true);
+        let text = "The cat and THE dog\nthe end\n";
+        assert!(table.read(Cursor::new(text)).is_ok());
+        assert_eq!(table.counts.get("the"), Some(&3));
+        assert_eq!(table.counts.get("dog"), Some(&1));
+        assert_eq!(table.counts.values().sum::<usize>(), 7);
+    }
+}
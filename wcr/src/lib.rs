@@ -1,26 +1,60 @@
 use clap::{App, Arg};
+use serde::Serialize;
 use std::error::Error;
-use std::fs::File;
+use std::ffi::{OsStr, OsString};
+use std::fmt::Display;
+use std::fs::{self, File};
 use std::io::{self, BufRead, BufReader};
 
+mod count;
+mod files;
+mod freq;
+mod output;
+mod parallel;
+mod stats;
+pub use count::{count, count_with, CountOptions};
+pub use files::FileList;
+pub use freq::FreqOptions;
+pub use output::{Format, Total};
+pub use stats::LineLengths;
+use files::FileNames;
+use freq::run_freq;
+use output::Output;
+use parallel::count_parallel;
+
 type MyResult<T> = Result<T, Box<dyn Error>>;
 
 // Struct que conterá os argumentos passados para o programa
 #[derive(Debug)] // derive implementa a trait automaticamente
 pub struct Config {
     files: Vec<String>,
+    // quando existe, os nomes vêm da lista e não de files
+    files_from: Option<FileList>,
     lines: bool,
     words: bool,
     bytes: bool,
     chars: bool,
+    max_line_length: bool,
+    stats: bool,
+    format: Format,
+    total: Total,
+    // quantidade de threads que contam os arquivos
+    jobs: usize,
+    // quando existe, mostra a frequência das palavras em vez das contagens
+    freq: Option<FreqOptions>,
 }
 
-#[derive(Debug, PartialEq)]
+// Serialize permite gravar as contagens em JSON ou CSV também a partir da
+// biblioteca; line_lengths vira o resumo mostrado por --stats
+#[derive(Debug, Default, PartialEq, Serialize)]
 pub struct FileInfo {
     num_lines: usize,
     num_words: usize,
     num_bytes: usize,
     num_chars: usize,
+    // largura da maior linha na tela, como no wc -L
+    max_line_length: usize,
+    line_lengths: LineLengths,
 }
 
 // --------------------------------------------------
@@ -36,6 +70,20 @@ pub fn get_args() -> MyResult<Config> {
                 .default_value("-") // STDIN
                 .multiple(true),
         )
+        .arg(
+            Arg::with_name("files0_from")
+                .long("files0-from")
+                .value_name("F")
+                .help("Read NUL-terminated file names from F (- for stdin)")
+                .conflicts_with_all(&["files", "files_from"]),
+        )
+        .arg(
+            Arg::with_name("files_from")
+                .long("files-from")
+                .value_name("F")
+                .help("Read newline-terminated file names from F")
+                .conflicts_with("files"),
+        )
         .arg(
             Arg::with_name("words")
                 .short("w")
@@ -66,6 +114,87 @@ pub fn get_args() -> MyResult<Config> {
                 .help("Show line count")
                 .takes_value(false), // flags que não recebem valores
         )
+        .arg(
+            Arg::with_name("max_line_length")
+                .short("L")
+                .long("max-line-length")
+                .help("Show maximum display width of a line")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("stats")
+                .long("stats")
+                .help("Show min, mean, median and max line width")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("format")
+                .long("format")
+                .value_name("FORMAT")
+                .help("Output format")
+                .possible_values(&["text", "json", "csv", "tsv"])
+                .default_value("text"),
+        )
+        .arg(
+            Arg::with_name("total")
+                .long("total")
+                .value_name("WHEN")
+                .help("When to print a line with total counts")
+                .possible_values(&["auto", "always", "only", "never"])
+                .default_value("auto"),
+        )
+        .arg(
+            Arg::with_name("jobs")
+                .short("j")
+                .long("jobs")
+                .value_name("N")
+                .help("Count N files at a time")
+                .default_value("1"),
+        )
+        .arg(
+            Arg::with_name("freq")
+                .long("freq")
+                .help("Show how many times each word appears")
+                .takes_value(false)
+                .conflicts_with_all(&[
+                    "lines",
+                    "words",
+                    "bytes",
+                    "chars",
+                    "max_line_length",
+                    "stats",
+                    "total",
+                    "jobs",
+                ]),
+        )
+        .arg(
+            Arg::with_name("top")
+                .long("top")
+                .value_name("N")
+                .help("Show only the N most frequent words")
+                .requires("freq"),
+        )
+        .arg(
+            Arg::with_name("ignore_case")
+                .long("ignore-case")
+                .help("Count words ignoring case")
+                .takes_value(false)
+                .requires("freq"),
+        )
+        .arg(
+            Arg::with_name("min_length")
+                .long("min-length")
+                .value_name("N")
+                .help("Skip words shorter than N characters")
+                .requires("freq"),
+        )
+        .arg(
+            Arg::with_name("stopwords")
+                .long("stopwords")
+                .value_name("FILE")
+                .help("Skip the words listed in FILE")
+                .requires("freq"),
+        )
         .get_matches();
 
 
@@ -74,10 +203,13 @@ pub fn get_args() -> MyResult<Config> {
     let mut words = matches.is_present("words");
     let mut bytes = matches.is_present("bytes");
     let chars = matches.is_present("chars");
+    let max_line_length = matches.is_present("max_line_length");
+    let stats = matches.is_present("stats");
     
     // se todas as flags forem falsas, define por padrão que serão
     // mostradas linhas, palavras e bytes
-    if [words, bytes, chars, lines].iter().all(|v| v == &false) {
+    let flags = [words, bytes, chars, lines, max_line_length, stats];
+    if flags.iter().all(|v| v == &false) {
         // transforma um slice em iterador e o iter retorna uma
         // referencia para os valores, então por isso checamos
         // contra uma referencia para false
@@ -88,115 +220,293 @@ pub fn get_args() -> MyResult<Config> {
         bytes = true;
     }
 
+    let files_from = match (
+        matches.value_of("files0_from"),
+        matches.value_of("files_from"),
+    ) {
+        (Some(path), _) => Some(FileList {
+            path: path.to_string(),
+            separator: b'\0',
+        }),
+        (_, Some(path)) => Some(FileList {
+            path: path.to_string(),
+            separator: b'\n',
+        }),
+        _ => None,
+    };
+
+    let jobs = matches
+        .value_of("jobs")
+        .map(parse_positive_int)
+        .transpose()
+        .map_err(|e| format!("illegal job count -- {}", e))?
+        .unwrap();
+
+    let freq = if matches.is_present("freq") {
+        let top = matches
+            .value_of("top")
+            .map(parse_positive_int)
+            .transpose()
+            .map_err(|e| format!("illegal top count -- {}", e))?;
+        let min_length = matches
+            .value_of("min_length")
+            .map(|val| val.parse())
+            .transpose()
+            .map_err(|_| {
+                format!(
+                    "illegal minimum length -- {}",
+                    matches.value_of("min_length").unwrap_or_default()
+                )
+            })?
+            .unwrap_or(0);
+        Some(FreqOptions {
+            top,
+            ignore_case: matches.is_present("ignore_case"),
+            min_length,
+            stopwords: matches.value_of("stopwords").map(String::from),
+        })
+    } else {
+        None
+    };
+
     Ok(Config {
         files: matches.values_of_lossy("files").unwrap(),
+        files_from,
         lines,
         words,
         bytes,
         chars,
+        max_line_length,
+        stats,
+        // possible_values garante que o valor é um dos quatro
+        format: match matches.value_of("format") {
+            Some("json") => Format::Json,
+            Some("csv") => Format::Csv,
+            Some("tsv") => Format::Tsv,
+            _ => Format::Text,
+        },
+        total: match matches.value_of("total") {
+            Some("always") => Total::Always,
+            Some("only") => Total::Only,
+            Some("never") => Total::Never,
+            _ => Total::Auto,
+        },
+        jobs,
+        freq,
     })
 }
 
+// --------------------------------------------------
+fn parse_positive_int(val: &str) -> MyResult<usize> {
+    match val.parse() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(From::from(val)),
+    }
+}
+
 // --------------------------------------------------
 pub fn run(config: Config) -> MyResult<()> {
-    let mut total_lines = 0;
-    let mut total_words = 0;
-    let mut total_bytes = 0;
-    let mut total_chars = 0;
-
-    for filename in &config.files {
-        match open(filename) {
-            Err(err) => eprintln!("{}: {}", filename, err),
-             Ok(file) => {
-                if let Ok(info) = count(file) {
-                    println!(
-                        "{}{}{}{}{}",
-                        format_field(info.num_lines, config.lines),
-                        format_field(info.num_words, config.words),
-                        format_field(info.num_bytes, config.bytes),
-                        format_field(info.num_chars, config.chars),
-                        if filename == "-" {
-                            "".to_string()
-                        } else {
-                            format!(" {}", &filename)
-                        },
-                    );
-
-                    total_lines += info.num_lines;
-                    total_words += info.num_words;
-                    total_bytes += info.num_bytes;
-                    total_chars += info.num_chars;
+    // os nomes de uma lista grande são lidos conforme os arquivos são
+    // contados, então não há como calcular a largura das colunas antes
+    let mut names = match &config.files_from {
+        Some(list) => FileNames::from_list(list)?,
+        None => FileNames::args(config.files.clone()),
+    };
+    if let Some(options) = &config.freq {
+        return run_freq(names, options, config.format);
+    }
+
+    let width = names
+        .loaded()
+        .map_or(1, |files| column_width(files, &config));
+    let mut output = Output::new(config.format, width);
+    let mut total = FileInfo::default();
+
+    let options = CountOptions {
+        chars: config.chars,
+        line_widths: config.max_line_length || config.stats,
+    };
+    // os resultados chegam aqui na ordem dos nomes, com ou sem -j
+    let mut report = |filename: &OsStr, counted: Counted| -> MyResult<()> {
+        match counted {
+            Err(err) => eprintln!("{}", err),
+            Ok(Some(info)) => {
+                if config.total != Total::Only {
+                    // o nome só vira texto na saída
+                    let filename = filename.to_string_lossy();
+                    output.write(&filename, &info, &config)?;
                 }
+                total.add(&info);
             }
+            Ok(None) => {}
+        }
+        Ok(())
+    };
+    // não adianta ter mais threads que arquivos
+    let jobs = names
+        .loaded()
+        .map_or(config.jobs, |files| config.jobs.min(files.len()));
+    if jobs > 1 {
+        count_parallel(names.by_ref(), jobs, options, &mut report)?;
+    } else {
+        for filename in names.by_ref() {
+            let filename = filename?;
+            report(&filename, count_file(&filename, options))?;
         }
     }
 
-    if config.files.len() > 1 {
-        println!(
-            "{}{}{}{} total",
-            format_field(total_lines, config.lines),
-            format_field(total_words, config.words),
-            format_field(total_bytes, config.bytes),
-            format_field(total_chars, config.chars)
-        );
+    let show_total = match config.total {
+        Total::Auto => names.seen() > 1,
+        Total::Always | Total::Only => true,
+        Total::Never => false,
+    };
+    if show_total {
+        output.write_total(&total, &config)?;
     }
 
-    Ok(())
+    output.finish()
 }
 
 // --------------------------------------------------
-fn open(filename: &str) -> MyResult<Box<dyn BufRead>> {
-    match filename {
-        "-" => Ok(Box::new(BufReader::new(io::stdin()))),
-        _ => Ok(Box::new(BufReader::new(File::open(filename)?))),
+// Resultado da contagem de um arquivo: a mensagem de erro quando ele não pode
+// ser aberto, ou None quando a leitura falha no meio. É uma String, e não um
+// Box<dyn Error>, para poder vir das threads de -j
+type Counted = Result<Option<FileInfo>, String>;
+
+fn count_file(filename: &OsStr, options: CountOptions) -> Counted {
+    match open(filename) {
+        Err(err) => Err(format!("{}: {}", filename.to_string_lossy(), err)),
+        Ok(file) => Ok(count_with(file, options).ok()),
     }
 }
 
 // --------------------------------------------------
-fn format_field(value: usize, show: bool) -> String {
-    if show {
-        format!("{:>8}", value)
+fn open(filename: &OsStr) -> MyResult<Box<dyn BufRead>> {
+    if filename == "-" {
+        Ok(Box::new(BufReader::new(io::stdin())))
     } else {
-        "".to_string()
+        Ok(Box::new(BufReader::new(File::open(filename)?)))
     }
 }
 
 // --------------------------------------------------
-pub fn count(mut file: impl BufRead) -> MyResult<FileInfo> {
-    let mut num_lines = 0;
-    let mut num_words = 0;
-    let mut num_bytes = 0;
-    let mut num_chars = 0;
-    let mut line = String::new(); // buffer
-
-    loop {
-         // read_line retorna a quantidade de bytes lidos
-         let line_bytes = file.read_line(&mut line)?;
-         if line_bytes == 0 {
-             // arquivo vazio
-             break;
-         }
-         num_bytes += line_bytes;
-         num_lines += 1;
-         // split_whitespace e chars retornam iterators e
-         // Iterator::count retorna o total de itens
-         num_words += line.split_whitespace().count();
-         num_chars += line.chars().count();
-         // limpa o buffer para leitura posterior
-         line.clear();
+impl FileInfo {
+    // soma as contagens de outro arquivo, para a linha de total
+    fn add(&mut self, other: &FileInfo) {
+        self.num_lines += other.num_lines;
+        self.num_words += other.num_words;
+        self.num_bytes += other.num_bytes;
+        self.num_chars += other.num_chars;
+        // no total, -L mostra a maior linha entre os arquivos
+        self.max_line_length = self.max_line_length.max(other.max_line_length);
+        self.line_lengths.merge(&other.line_lengths);
     }
+}
 
-    Ok(FileInfo {
-        num_lines,
-        num_words,
-        num_bytes,
-        num_chars,
-    })
+// --------------------------------------------------
+// Largura das colunas, calculada como no wc do GNU: a quantidade de dígitos
+// da soma dos tamanhos dos arquivos regulares, que nenhuma contagem de bytes,
+// nem o total, pode ultrapassar. Com um pipe ou dispositivo, cujo tamanho não
+// se sabe antes da leitura, a largura é no mínimo 7. Não há o que alinhar com
+// uma única coluna de um único arquivo, nem com --total=only. Com --stats,
+// as colunas são alargadas para caber as casas decimais
+fn column_width(files: &[OsString], config: &Config) -> usize {
+    let columns = [
+        config.lines,
+        config.words,
+        config.bytes,
+        config.chars,
+        config.max_line_length,
+    ]
+    .iter()
+    .filter(|&&show| show)
+    .count()
+        + if config.stats { 4 } else { 0 };
+    if config.total == Total::Only
+        || (files.len() == 1 && columns == 1)
+    {
+        return 1;
+    }
+
+    let mut min_width = 1;
+    let mut regular_total: u64 = 0;
+    for filename in files {
+        let metadata = if filename == "-" {
+            stdin_metadata()
+        } else {
+            fs::metadata(filename)
+        };
+        // arquivos que não existem são ignorados, o erro aparece depois
+        match metadata {
+            Ok(metadata) if metadata.is_file() => {
+                regular_total += metadata.len()
+            }
+            Ok(_) => min_width = 7,
+            Err(_) => {}
+        }
+    }
+    let width = regular_total.to_string().len().max(min_width);
+    // a média e a mediana de --stats têm uma casa decimal, então ocupam até
+    // dois caracteres a mais que a maior contagem
+    if config.stats {
+        width + 2
+    } else {
+        width
+    }
+}
+
+// --------------------------------------------------
+// O stdin pode ser um arquivo regular (wcr < arquivo.txt), então o tamanho
+// vem do próprio descritor
+#[cfg(unix)]
+pub(crate) fn stdin_metadata() -> io::Result<fs::Metadata> {
+    use std::os::fd::AsFd;
+    let fd = io::stdin().as_fd().try_clone_to_owned()?;
+    File::from(fd).metadata()
+}
+
+#[cfg(not(unix))]
+pub(crate) fn stdin_metadata() -> io::Result<fs::Metadata> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+// --------------------------------------------------
+// Colunas de uma linha da saída de texto, na ordem do wc, separadas por um
+// espaço. As colunas de --stats vêm por último: menor, média, mediana e maior
+// largura de linha; a média e a mediana podem ser fracionárias e são
+// mostradas com uma casa decimal
+fn format_counts(info: &FileInfo, config: &Config, width: usize) -> String {
+    let lengths = &info.line_lengths;
+    let stats = config.stats;
+    [
+        format_field(info.num_lines, config.lines, width),
+        format_field(info.num_words, config.words, width),
+        format_field(info.num_bytes, config.bytes, width),
+        format_field(info.num_chars, config.chars, width),
+        format_field(info.max_line_length, config.max_line_length, width),
+        format_field(lengths.min(), stats, width),
+        format_field(format!("{:.1}", lengths.mean()), stats, width),
+        format_field(format!("{:.1}", lengths.median()), stats, width),
+        format_field(lengths.max(), stats, width),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>()
+    .join(" ")
+}
+
+// --------------------------------------------------
+fn format_field(
+    value: impl Display,
+    show: bool,
+    width: usize,
+) -> Option<String> {
+    show.then(|| format!("{:>1$}", value, width))
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{count, FileInfo};
+    use super::{count, FileInfo, LineLengths};
     // Cursor é usado para buffers em memória para implementar as traits
     // Read e Write em qualquer valor que implemente AsRef<[u8]>, para
     // para os buffers possam ser usados em qualquer lugar que se use um
@@ -211,19 +521,48 @@ mod tests {
         // criamos um arquivo fake a partir do texto acima
         let info = count(Cursor::new(text));
         assert!(info.is_ok()); // checa se o Result é Ok
+        let mut line_lengths = LineLengths::default();
+        line_lengths.record(46);
         let expected = FileInfo {
             num_lines: 1,
             num_words: 10,
             num_bytes: 48,
             num_chars: 48,
+            max_line_length: 46,
+            line_lengths,
         };
         assert_eq!(info.unwrap(), expected);
     }
 
     #[test]
     fn test_format_field() {
-        assert_eq!(format_field(1, false), "");
-        assert_eq!(format_field(3, true), "       3");
-        assert_eq!(format_field(10, true), "      10");
+        assert_eq!(format_field(1, false, 8), None);
+        assert_eq!(format_field(3, true, 8).unwrap(), "       3");
+        assert_eq!(format_field(10, true, 8).unwrap(), "      10");
+        assert_eq!(format_field("9.4", true, 8).unwrap(), "     9.4");
+        assert_eq!(format_field(10, true, 1).unwrap(), "10");
+    }
+
+    #[test]
+    fn test_serialize() {
+        let info = count(Cursor::new("a\tb\n")).unwrap();
+        let json = serde_json::to_string(&info).unwrap();
+        assert_eq!(
+            json,
+            "{\"num_lines\":1,\"num_words\":2,\"num_bytes\":4,\
+             \"num_chars\":4,\"max_line_length\":9,\"line_lengths\":\
+             {\"min\":9,\"mean\":9.0,\"median\":9.0,\"max\":9}}"
+        );
+    }
+
+    #[test]
+    fn test_line_width() {
+        let line_width =
+            |text| count(Cursor::new(text)).unwrap().max_line_length;
+        assert_eq!(line_width(""), 0);
+        assert_eq!(line_width("fox\n"), 3);
+        assert_eq!(line_width("a\tb\tc"), 17);
+        assert_eq!(line_width("日本語"), 6);
+        assert_eq!(line_width("long line\rshort"), 9);
     }
 }
@@ -0,0 +1,159 @@
+use super::{format_counts, Config, FileInfo, MyResult};
+use serde::Serialize;
+use std::io::{self, Stdout, Write};
+
+// Formatos de saída de --format
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Text,
+    Json,
+    Csv,
+    Tsv,
+}
+
+// Quando mostrar a linha de total, como em --total do wc do GNU: com Auto,
+// só quando há mais de um arquivo; com Only, o total é a única linha
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Total {
+    Auto,
+    Always,
+    Only,
+    Never,
+}
+
+// --------------------------------------------------
+// Uma linha da saída nos formatos estruturados. Todas as colunas aparecem
+// sempre, para que o formato não mude com as opções: as medidas que não foram
+// pedidas ficam null no JSON e vazias no CSV e no TSV
+#[derive(Debug, Serialize)]
+struct Record<'a> {
+    file: &'a str,
+    lines: Option<usize>,
+    words: Option<usize>,
+    bytes: Option<usize>,
+    chars: Option<usize>,
+    max_line_length: Option<usize>,
+    line_width_min: Option<usize>,
+    line_width_mean: Option<f64>,
+    line_width_median: Option<f64>,
+    line_width_max: Option<usize>,
+}
+
+impl<'a> Record<'a> {
+    fn new(file: &'a str, info: &FileInfo, config: &Config) -> Self {
+        let lengths = &info.line_lengths;
+        Record {
+            file,
+            lines: config.lines.then_some(info.num_lines),
+            words: config.words.then_some(info.num_words),
+            bytes: config.bytes.then_some(info.num_bytes),
+            chars: config.chars.then_some(info.num_chars),
+            max_line_length: config
+                .max_line_length
+                .then_some(info.max_line_length),
+            line_width_min: config.stats.then(|| lengths.min()),
+            line_width_mean: config.stats.then(|| lengths.mean()),
+            line_width_median: config.stats.then(|| lengths.median()),
+            line_width_max: config.stats.then(|| lengths.max()),
+        }
+    }
+}
+
+// --------------------------------------------------
+// Escreve o resultado de cada arquivo assim que ele é contado, no formato de
+// --format. Nos formatos estruturados, o total é escrito como um arquivo
+// chamado "total"
+pub(crate) enum Output {
+    // width é a largura das colunas
+    Text { out: Stdout, width: usize },
+    // o JSON é um array, então é preciso saber se já há um objeto antes
+    Json { out: Stdout, empty: bool },
+    // o csv::Writer guarda o próprio buffer, então fica em uma Box para não
+    // aumentar o tamanho das outras variantes
+    Delimited(Box<csv::Writer<Stdout>>),
+}
+
+impl Output {
+    pub fn new(format: Format, width: usize) -> Self {
+        match format {
+            Format::Text => Output::Text {
+                out: io::stdout(),
+                width,
+            },
+            Format::Json => Output::Json {
+                out: io::stdout(),
+                empty: true,
+            },
+            Format::Csv | Format::Tsv => {
+                let delimiter =
+                    if format == Format::Csv { b',' } else { b'\t' };
+                // o cabeçalho vem dos nomes dos campos de Record
+                Output::Delimited(Box::new(
+                    csv::WriterBuilder::new()
+                        .delimiter(delimiter)
+                        .from_writer(io::stdout()),
+                ))
+            }
+        }
+    }
+
+    pub fn write(
+        &mut self,
+        file: &str,
+        info: &FileInfo,
+        config: &Config,
+    ) -> MyResult<()> {
+        match self {
+            Output::Text { out, width } => writeln!(
+                out,
+                "{}{}",
+                format_counts(info, config, *width),
+                if file == "-" {
+                    "".to_string()
+                } else {
+                    format!(" {}", file)
+                },
+            )?,
+            Output::Json { out, empty } => {
+                let record = Record::new(file, info, config);
+                let separator = if *empty { "[\n" } else { ",\n" };
+                let json = serde_json::to_string(&record)?;
+                write!(out, "{}  {}", separator, json)?;
+                *empty = false;
+            }
+            Output::Delimited(writer) => {
+                writer.serialize(Record::new(file, info, config))?;
+                // as linhas aparecem conforme os arquivos são contados
+                writer.flush()?;
+            }
+        }
+        Ok(())
+    }
+
+    // Com --total=only, a linha de texto não tem o nome "total", já que não
+    // há outras linhas para diferenciar
+    pub fn write_total(
+        &mut self,
+        info: &FileInfo,
+        config: &Config,
+    ) -> MyResult<()> {
+        match self {
+            Output::Text { out, width } if config.total == Total::Only => {
+                writeln!(out, "{}", format_counts(info, config, *width))?;
+                Ok(())
+            }
+            _ => self.write("total", info, config),
+        }
+    }
+
+    pub fn finish(self) -> MyResult<()> {
+        match self {
+            Output::Text { .. } => {}
+            Output::Json { mut out, empty } => {
+                writeln!(out, "{}", if empty { "[]" } else { "\n]" })?
+            }
+            Output::Delimited(mut writer) => writer.flush()?,
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,88 @@
+use super::{count_file, Counted, CountOptions, MyResult};
+use std::collections::BTreeMap;
+use std::ffi::{OsStr, OsString};
+use std::num::NonZeroUsize;
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+// A leitura dos arquivos deixa as threads paradas boa parte do tempo, então
+// vale ter algumas a mais que o número de processadores
+const JOBS_PER_CPU: usize = 4;
+
+// --------------------------------------------------
+// Conta os arquivos em jobs threads e entrega os resultados a report na
+// ordem dos nomes, como se fossem contados um depois do outro. Cada
+// resultado sai assim que todos os anteriores saíram, e no máximo
+// 2 * jobs arquivos ficam entre o envio e a saída: os nomes de uma lista
+// longa não são lidos antes da hora e um arquivo grande no começo não faz os
+// resultados seguintes se acumularem na memória. Mais threads que
+// JOBS_PER_CPU por processador não ajudam, então jobs é limitado a isso
+pub(crate) fn count_parallel(
+    names: impl Iterator<Item = MyResult<OsString>>,
+    jobs: usize,
+    options: CountOptions,
+    mut report: impl FnMut(&OsStr, Counted) -> MyResult<()>,
+) -> MyResult<()> {
+    let cpus = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+    let jobs = jobs.min(cpus.saturating_mul(JOBS_PER_CPU));
+    let window = jobs.saturating_mul(2);
+    // os workers disputam a mesma fila, então o receptor fica em um Mutex
+    let (work_tx, work_rx) = mpsc::sync_channel::<(usize, OsString)>(window);
+    let work_rx = Arc::new(Mutex::new(work_rx));
+    let (result_tx, result_rx) = mpsc::channel();
+
+    let workers = (0..jobs)
+        .map(|_| {
+            let work_rx = Arc::clone(&work_rx);
+            let result_tx = result_tx.clone();
+            thread::Builder::new().spawn(move || loop {
+                // o lock é liberado antes da contagem, ao fim da instrução
+                let job = work_rx.lock().unwrap().recv();
+                // a fila só fecha quando não há mais nomes
+                let Ok((index, filename)) = job else { break };
+                let counted = count_file(&filename, options);
+                if result_tx.send((index, filename, counted)).is_err() {
+                    break;
+                }
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("cannot start a counting thread: {}", e))?;
+    // sem essa cópia, result_rx nunca fecharia se um worker falhasse
+    drop(result_tx);
+
+    let mut names = names.peekable();
+    // resultados que chegaram antes dos anteriores, por posição
+    let mut pending = BTreeMap::new();
+    let mut sent = 0;
+    let mut next = 0;
+    loop {
+        while sent - next < window && names.peek().is_some() {
+            if let Some(filename) = names.next() {
+                work_tx.send((sent, filename?))?;
+                sent += 1;
+            }
+        }
+        if next == sent {
+            break;
+        }
+
+        let (index, filename, counted) = result_rx
+            .recv()
+            .map_err(|_| "a counting thread stopped unexpectedly")?;
+        pending.insert(index, (filename, counted));
+        while let Some((filename, counted)) = pending.remove(&next) {
+            report(&filename, counted)?;
+            next += 1;
+        }
+    }
+
+    drop(work_tx);
+    for worker in workers {
+        worker
+            .join()
+            .map_err(|_| "a counting thread stopped unexpectedly")?;
+    }
+    Ok(())
+}
@@ -0,0 +1,108 @@
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use std::collections::BTreeMap;
+
+// Comprimentos das linhas de um arquivo, usados por --stats. Em vez de guardar
+// cada linha, guarda quantas linhas têm cada comprimento: a mediana continua
+// exata e a memória depende apenas da quantidade de comprimentos diferentes
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct LineLengths {
+    counts: BTreeMap<usize, usize>,
+    num_lines: usize,
+    total: usize,
+}
+
+impl LineLengths {
+    pub fn record(&mut self, length: usize) {
+        *self.counts.entry(length).or_insert(0) += 1;
+        self.num_lines += 1;
+        self.total += length;
+    }
+
+    // junta os comprimentos de outro arquivo, para a linha de total
+    pub fn merge(&mut self, other: &LineLengths) {
+        for (&length, &count) in &other.counts {
+            *self.counts.entry(length).or_insert(0) += count;
+        }
+        self.num_lines += other.num_lines;
+        self.total += other.total;
+    }
+
+    // as chaves do BTreeMap ficam ordenadas, então o menor e o maior
+    // comprimento são a primeira e a última chave. Sem linhas, tudo é zero
+    pub fn min(&self) -> usize {
+        self.counts.keys().next().copied().unwrap_or(0)
+    }
+
+    pub fn max(&self) -> usize {
+        self.counts.keys().next_back().copied().unwrap_or(0)
+    }
+
+    pub fn mean(&self) -> f64 {
+        if self.num_lines == 0 {
+            0.0
+        } else {
+            self.total as f64 / self.num_lines as f64
+        }
+    }
+
+    // com uma quantidade par de linhas, a mediana é a média das duas do meio
+    pub fn median(&self) -> f64 {
+        if self.num_lines == 0 {
+            return 0.0;
+        }
+        let lower = self.nth((self.num_lines - 1) / 2);
+        let upper = self.nth(self.num_lines / 2);
+        (lower + upper) as f64 / 2.0
+    }
+
+    // comprimento da n-ésima linha (a partir de 0) em ordem crescente
+    fn nth(&self, n: usize) -> usize {
+        let mut seen = 0;
+        for (&length, &count) in &self.counts {
+            seen += count;
+            if n < seen {
+                return length;
+            }
+        }
+        self.max()
+    }
+}
+
+// Serializa apenas o resumo, com os mesmos valores das colunas de --stats
+impl Serialize for LineLengths {
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut summary = serializer.serialize_struct("LineLengths", 4)?;
+        summary.serialize_field("min", &self.min())?;
+        summary.serialize_field("mean", &self.mean())?;
+        summary.serialize_field("median", &self.median())?;
+        summary.serialize_field("max", &self.max())?;
+        summary.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LineLengths;
+
+    #[test]
+    fn test_line_lengths() {
+        let mut lengths = LineLengths::default();
+        assert_eq!(lengths.median(), 0.0);
+        for length in [12, 6, 0, 12, 17] {
+            lengths.record(length);
+        }
+        assert_eq!(lengths.min(), 0);
+        assert_eq!(lengths.max(), 17);
+        assert_eq!(lengths.mean(), 9.4);
+        assert_eq!(lengths.median(), 12.0);
+
+        let mut other = LineLengths::default();
+        other.record(1);
+        lengths.merge(&other);
+        assert_eq!(lengths.min(), 0);
+        assert_eq!(lengths.median(), 9.0);
+    }
+}
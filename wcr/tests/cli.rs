@@ -1,7 +1,11 @@
-use assert_cmd::Command;
+// os testes passam os argumentos como .args(&[...]), como nos outros crates
+#![allow(clippy::needless_borrows_for_generic_args)]
+
+use assert_cmd::{assert::OutputAssertExt, cargo::cargo_bin, Command};
 use predicates::prelude::*;
 use rand::{distributions::Alphanumeric, Rng};
-use std::fs;
+use std::fs::{self, File};
+use std::process::{Command as StdCommand, Stdio};
 
 type TestResult = Result<(), Box<dyn std::error::Error>>;
 
@@ -9,6 +13,10 @@ const PRG: &str = "wcr";
 const EMPTY: &str = "tests/inputs/empty.txt";
 const FOX: &str = "tests/inputs/fox.txt";
 const ATLAMAL: &str = "tests/inputs/atlamal.txt";
+const WIDE: &str = "tests/inputs/wide.txt";
+const INVALID: &str = "tests/inputs/invalid.txt";
+const WORDS: &str = "tests/inputs/freq.txt";
+const STOPWORDS: &str = "tests/inputs/stopwords.txt";
 
 // --------------------------------------------------
 fn gen_bad_file() -> String {
@@ -161,9 +169,22 @@ fn atlamal_bytes_lines() -> TestResult {
 // --------------------------------------------------
 #[test]
 fn atlamal_stdin() -> TestResult {
-    let input = fs::read_to_string(ATLAMAL)?;
+    // o arquivo é o próprio stdin, como em wcr < atlamal.txt
     let expected =
         fs::read_to_string("tests/expected/atlamal.txt.stdin.out")?;
+    StdCommand::new(cargo_bin(PRG))
+        .stdin(Stdio::from(File::open(ATLAMAL)?))
+        .assert()
+        .success()
+        .stdout(expected);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn atlamal_pipe() -> TestResult {
+    let input = fs::read_to_string(ATLAMAL)?;
+    let expected = fs::read_to_string("tests/expected/atlamal.txt.pipe.out")?;
     Command::cargo_bin(PRG)?
         .write_stdin(input)
         .assert()
@@ -212,3 +233,429 @@ fn test_all_words_lines() -> TestResult {
 fn test_all_bytes_lines() -> TestResult {
     run(&["-cl", EMPTY, FOX, ATLAMAL], "tests/expected/all.cl.out")
 }
+
+// --------------------------------------------------
+#[test]
+fn fox_max_line_length() -> TestResult {
+    run(&["-L", FOX], "tests/expected/fox.txt.L.out")
+}
+
+// --------------------------------------------------
+// tabs e caracteres largos contam pela largura na tela
+#[test]
+fn wide_max_line_length() -> TestResult {
+    run(&["--max-line-length", WIDE], "tests/expected/wide.txt.L.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn test_all_max_line_length() -> TestResult {
+    run(&["-L", EMPTY, FOX, ATLAMAL, WIDE], "tests/expected/all.L.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn wide_stats() -> TestResult {
+    run(&["--stats", WIDE], "tests/expected/wide.txt.stats.out")
+}
+
+// --------------------------------------------------
+// no total, as estatísticas consideram as linhas de todos os arquivos
+#[test]
+fn test_all_lines_stats() -> TestResult {
+    run(
+        &["-l", "--stats", EMPTY, FOX, ATLAMAL, WIDE],
+        "tests/expected/all.l.stats.out",
+    )
+}
+
+// --------------------------------------------------
+// UTF-8 inválido não interrompe a contagem
+#[test]
+fn invalid() -> TestResult {
+    run(&[INVALID], "tests/expected/invalid.txt.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn invalid_chars() -> TestResult {
+    run(&["-lwm", INVALID], "tests/expected/invalid.txt.lwm.out")
+}
+
+// --------------------------------------------------
+// as medidas que não foram pedidas aparecem como null
+#[test]
+fn test_all_json() -> TestResult {
+    run(
+        &["--format", "json", EMPTY, FOX, ATLAMAL],
+        "tests/expected/all.json.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn fox_wide_stats_csv() -> TestResult {
+    run(
+        &["--format=csv", "-l", "--stats", FOX, WIDE],
+        "tests/expected/fox_wide.l.stats.csv.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn wide_stdin_tsv() -> TestResult {
+    let input = fs::read_to_string(WIDE)?;
+    let expected =
+        fs::read_to_string("tests/expected/wide.txt.mL.stdin.tsv.out")?;
+    Command::cargo_bin(PRG)?
+        .args(["--format", "tsv", "-mL"])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(expected);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn json_without_files() -> TestResult {
+    let bad = gen_bad_file();
+    Command::cargo_bin(PRG)?
+        .args(["--format", "json", &bad])
+        .assert()
+        .success()
+        .stdout("[]\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_format() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["--format", "xml", FOX])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "'xml' isn't a valid value for '--format <FORMAT>'",
+        ));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn fox_total_always() -> TestResult {
+    run(
+        &["--total", "always", FOX],
+        "tests/expected/fox.txt.total-always.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn test_all_total_only() -> TestResult {
+    run(
+        &["--total=only", EMPTY, FOX, ATLAMAL],
+        "tests/expected/all.total-only.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn test_all_lines_total_never() -> TestResult {
+    run(
+        &["-l", "--total", "never", EMPTY, FOX, ATLAMAL],
+        "tests/expected/all.l.total-never.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn fox_atlamal_total_only_json() -> TestResult {
+    run(
+        &["--total", "only", "--format", "json", FOX, ATLAMAL],
+        "tests/expected/all.total-only.json.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_total() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["--total", "sometimes", FOX])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "'sometimes' isn't a valid value for '--total <WHEN>'",
+        ));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn files0_from() -> TestResult {
+    run(
+        &["--files0-from", "tests/inputs/files0-from.list"],
+        "tests/expected/files0-from.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn files0_from_stdin() -> TestResult {
+    let input = fs::read_to_string("tests/inputs/files0-from.list")?;
+    let expected = fs::read_to_string("tests/expected/files0-from.stdin.out")?;
+    Command::cargo_bin(PRG)?
+        .arg("--files0-from=-")
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(expected);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[cfg(unix)]
+#[test]
+fn files0_from_non_utf8_name() -> TestResult {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+
+    // os bytes do nome chegam intactos ao open, e só a saída é convertida
+    let dir = tempfile::tempdir()?;
+    let name = b"n\xe9.txt";
+    fs::copy(FOX, dir.path().join(OsStr::from_bytes(name)))?;
+    let mut input = name.to_vec();
+    input.push(b'\0');
+    Command::cargo_bin(PRG)?
+        .current_dir(dir.path())
+        .arg("--files0-from=-")
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout("1 9 48 n\u{fffd}.txt\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn files_from() -> TestResult {
+    run(
+        &["--files-from", "tests/inputs/files-from.list"],
+        "tests/expected/files0-from.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn files0_from_skips_bad_names() -> TestResult {
+    let input = format!("{}\0\0-\0", FOX);
+    Command::cargo_bin(PRG)?
+        .arg("--files0-from=-")
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout("1 9 48 tests/inputs/fox.txt\n1 9 48 total\n")
+        .stderr(predicate::str::contains(
+            "-:2: invalid zero-length file name",
+        ))
+        .stderr(predicate::str::contains(
+            "-:3: when reading file names from stdin",
+        ));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_files0_from_and_file() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["--files0-from", "tests/inputs/files0-from.list", FOX])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_files0_from() -> TestResult {
+    let bad = gen_bad_file();
+    Command::cargo_bin(PRG)?
+        .args(["--files0-from", &bad])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(format!(
+            "cannot open {} for reading",
+            bad
+        )));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn test_all_jobs() -> TestResult {
+    run(&["-j", "2", EMPTY, FOX, ATLAMAL], "tests/expected/all.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn jobs_keep_order() -> TestResult {
+    // arquivos de tamanhos diferentes terminam fora de ordem, e a saída
+    // precisa ser igual à contagem sem -j, inclusive os erros
+    let bad = gen_bad_file();
+    let mut files = vec![];
+    for _ in 0..20 {
+        files.extend([ATLAMAL, EMPTY, &bad, FOX, WIDE, INVALID]);
+    }
+    let sequential = Command::cargo_bin(PRG)?.args(&files).output()?;
+    Command::cargo_bin(PRG)?
+        .args(["--jobs", "4"])
+        .args(&files)
+        .assert()
+        .success()
+        .stdout(sequential.stdout)
+        .stderr(String::from_utf8(sequential.stderr)?);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn huge_jobs() -> TestResult {
+    // -j é limitado pelo número de arquivos e de processadores, então um
+    // valor enorme não cria uma thread para cada
+    run(
+        &["-j", "100000000000", EMPTY, FOX, ATLAMAL],
+        "tests/expected/all.out",
+    )?;
+    let input = fs::read_to_string("tests/inputs/files0-from.list")?;
+    let expected = fs::read_to_string("tests/expected/files0-from.stdin.out")?;
+    Command::cargo_bin(PRG)?
+        .args(["-j", "100000", "--files0-from=-"])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(expected);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_jobs() -> TestResult {
+    for bad in ["0", "many"] {
+        Command::cargo_bin(PRG)?
+            .args(["-j", bad, FOX])
+            .assert()
+            .failure()
+            .stderr(format!("illegal job count -- {}\n", bad));
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn freq() -> TestResult {
+    run(&["--freq", WORDS], "tests/expected/freq.txt.freq.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn freq_ignore_case_top() -> TestResult {
+    run(
+        &["--freq", "--ignore-case", "--top", "4", WORDS, FOX],
+        "tests/expected/freq_fox.freq.top4.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn freq_stopwords_min_length() -> TestResult {
+    run(
+        &[
+            "--freq",
+            "--ignore-case",
+            "--min-length",
+            "3",
+            "--stopwords",
+            STOPWORDS,
+            WORDS,
+            FOX,
+        ],
+        "tests/expected/freq_fox.freq.stopwords.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn freq_json() -> TestResult {
+    run(
+        &["--freq", "--format", "json", "--top", "2", "--ignore-case", WORDS],
+        "tests/expected/freq.txt.freq.json.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn freq_matches_word_count() -> TestResult {
+    // a soma da tabela é a contagem de -w
+    let expected = fs::read_to_string("tests/expected/all.w.out")?;
+    let words: usize = expected
+        .lines()
+        .last()
+        .and_then(|total| total.split_whitespace().next())
+        .unwrap()
+        .parse()?;
+    let output = Command::cargo_bin(PRG)?
+        .args(["--freq", "--format", "csv", EMPTY, FOX, ATLAMAL])
+        .output()?;
+    let mut reader = csv::Reader::from_reader(output.stdout.as_slice());
+    let mut sum = 0;
+    for record in reader.records() {
+        sum += record?[1].parse::<usize>()?;
+    }
+    assert_eq!(sum, words);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_freq_and_lines() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["--freq", "-l", WORDS])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_top_without_freq() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["--top", "3", WORDS])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--freq"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_top() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["--freq", "--top", "0", WORDS])
+        .assert()
+        .failure()
+        .stderr("illegal top count -- 0\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_stopwords() -> TestResult {
+    let bad = gen_bad_file();
+    Command::cargo_bin(PRG)?
+        .args(["--freq", "--stopwords", &bad, WORDS])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(format!("{}: ", bad)));
+    Ok(())
+}